@@ -0,0 +1,211 @@
+//! Conversion between decimal UI amounts and token base units.
+
+use std::fmt;
+
+use anyhow::{bail, Result};
+
+/// Token amount given either in UI units (`"1.5"`) or in base units.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Amount {
+    /// Decimal string scaled by the mint decimals.
+    Ui(String),
+    /// Base units, used as is.
+    Raw(u64),
+}
+
+impl Amount {
+    /// Parses `amount` as base units if `raw` is set, as a UI amount otherwise.
+    pub fn parse(amount: &str, raw: bool) -> Result<Self> {
+        if raw {
+            match amount.parse() {
+                Ok(amount) => Ok(Self::Raw(amount)),
+                Err(_) => bail!("invalid base unit amount {}", amount),
+            }
+        } else {
+            split_ui_amount(amount)?;
+            Ok(Self::Ui(amount.to_string()))
+        }
+    }
+
+    pub fn to_base_units(&self, decimals: u8) -> Result<u64> {
+        match self {
+            Self::Ui(amount) => ui_amount_to_base_units(amount, decimals),
+            Self::Raw(amount) => Ok(*amount),
+        }
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ui(amount) => write!(f, "{}", amount),
+            Self::Raw(amount) => write!(f, "{} base units", amount),
+        }
+    }
+}
+
+/// Converts a decimal string such as `"1.5"` to base units of a mint with `decimals`.
+///
+/// Fails instead of rounding when `amount` has more significant fractional digits than
+/// `decimals`, and when the result does not fit in a `u64`.
+pub fn ui_amount_to_base_units(amount: &str, decimals: u8) -> Result<u64> {
+    let (whole, fraction) = split_ui_amount(amount)?;
+
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > decimals as usize {
+        bail!(
+            "amount {} has more than {} decimal places",
+            amount,
+            decimals
+        );
+    }
+
+    let base_units = scale_digits(whole, decimals as u32)
+        .zip(scale_digits(
+            fraction,
+            (decimals as usize - fraction.len()) as u32,
+        ))
+        .and_then(|(whole, fraction)| whole.checked_add(fraction))
+        .and_then(|base_units| u64::try_from(base_units).ok());
+
+    match base_units {
+        Some(base_units) => Ok(base_units),
+        None => bail!("amount {} overflows u64 at {} decimals", amount, decimals),
+    }
+}
+
+/// Formats `amount` base units as a decimal string without losing precision.
+pub fn base_units_to_ui_amount(amount: u64, decimals: u8) -> String {
+    let digits = format!("{:0>width$}", amount, width = decimals as usize + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals as usize);
+    let fraction = fraction.trim_end_matches('0');
+
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+/// Splits `amount` into its whole and fractional digits.
+fn split_ui_amount(amount: &str) -> Result<(&str, &str)> {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+        bail!("invalid amount {}, expected a decimal number", amount);
+    }
+
+    Ok((whole, fraction))
+}
+
+/// Parses a run of ASCII digits, empty meaning zero, and multiplies it by `10^exponent`.
+fn scale_digits(digits: &str, exponent: u32) -> Option<u128> {
+    match digits.trim_start_matches('0') {
+        "" => Some(0),
+        digits => digits
+            .parse::<u128>()
+            .ok()?
+            .checked_mul(10u128.checked_pow(exponent)?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scales_ui_amounts() {
+        assert_eq!(ui_amount_to_base_units("1.5", 6).unwrap(), 1_500_000);
+        assert_eq!(ui_amount_to_base_units("1", 0).unwrap(), 1);
+        assert_eq!(ui_amount_to_base_units(".5", 1).unwrap(), 5);
+        assert_eq!(ui_amount_to_base_units("2.", 2).unwrap(), 200);
+        assert_eq!(ui_amount_to_base_units("007.010", 3).unwrap(), 7_010);
+    }
+
+    #[test]
+    fn accepts_trailing_zeros_past_the_decimals() {
+        assert_eq!(ui_amount_to_base_units("1.500", 1).unwrap(), 15);
+        assert_eq!(ui_amount_to_base_units("3.000", 0).unwrap(), 3);
+    }
+
+    #[test]
+    fn rejects_digits_past_the_decimals() {
+        assert!(ui_amount_to_base_units("0.5", 0).is_err());
+        assert!(ui_amount_to_base_units("1.05", 1).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_amounts() {
+        for amount in ["", ".", "-1", "+1", "1e3", "1.2.3", " 1", "1,5", "0x10"] {
+            assert!(Amount::parse(amount, false).is_err(), "{:?}", amount);
+            assert!(ui_amount_to_base_units(amount, 6).is_err(), "{:?}", amount);
+        }
+        assert!(Amount::parse("1.5", true).is_err());
+        assert!(Amount::parse("-1", true).is_err());
+    }
+
+    #[test]
+    fn fits_u64_max() {
+        assert_eq!(
+            ui_amount_to_base_units("18446744073709551615", 0).unwrap(),
+            u64::MAX
+        );
+        assert_eq!(
+            ui_amount_to_base_units("18.446744073709551615", 18).unwrap(),
+            u64::MAX
+        );
+        assert!(ui_amount_to_base_units("18446744073709551616", 0).is_err());
+        assert!(ui_amount_to_base_units("18.446744073709551616", 18).is_err());
+    }
+
+    #[test]
+    fn rejects_overflow_at_high_decimals() {
+        assert_eq!(
+            ui_amount_to_base_units("1", 19).unwrap(),
+            10_000_000_000_000_000_000
+        );
+        assert!(ui_amount_to_base_units("2", 19).is_err());
+        assert!(ui_amount_to_base_units("1", 20).is_err());
+        assert!(ui_amount_to_base_units("1", 255).is_err());
+        assert!(ui_amount_to_base_units(&"9".repeat(60), 0).is_err());
+    }
+
+    #[test]
+    fn zero_at_any_decimals() {
+        assert_eq!(ui_amount_to_base_units("0", 255).unwrap(), 0);
+        assert_eq!(ui_amount_to_base_units("0.000", 255).unwrap(), 0);
+        assert_eq!(base_units_to_ui_amount(0, 255), "0");
+    }
+
+    #[test]
+    fn raw_amounts_ignore_decimals() {
+        let amount = Amount::parse("1500", true).unwrap();
+        assert_eq!(amount, Amount::Raw(1500));
+        assert_eq!(amount.to_base_units(6).unwrap(), 1500);
+    }
+
+    #[test]
+    fn formats_base_units() {
+        assert_eq!(base_units_to_ui_amount(1_500_000, 6), "1.5");
+        assert_eq!(base_units_to_ui_amount(5, 3), "0.005");
+        assert_eq!(base_units_to_ui_amount(42, 0), "42");
+        assert_eq!(base_units_to_ui_amount(100, 2), "1");
+    }
+
+    #[test]
+    fn round_trips() {
+        for decimals in [0, 1, 6, 9, 18, 19] {
+            for amount in [0, 1, 10, 123_456_789, u64::MAX] {
+                let ui_amount = base_units_to_ui_amount(amount, decimals);
+                assert_eq!(
+                    ui_amount_to_base_units(&ui_amount, decimals).unwrap(),
+                    amount,
+                    "{} at {} decimals",
+                    ui_amount,
+                    decimals
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,513 @@
+//! Recipient and result files of `spl-faucet airdrop-batch`.
+//!
+//! Recipients are `wallet,amount` rows with an optional header. Results hold one row per
+//! recipient with the signature or error of its airdrop, and rows that succeeded are skipped
+//! when the same recipients are airdropped again. Failed rows keep the signature of any
+//! transaction that was sent, which is checked before airdropping them again since it may
+//! have landed after failing to confirm.
+//!
+//! [`PackedTx`] packs the airdrops of consecutive rows into as few transactions as the packet
+//! size and compute limits allow.
+
+use std::{
+    collections::HashSet,
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    instruction::Instruction,
+    message::Message,
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    signature::{Signature, SIGNATURE_BYTES},
+};
+
+use crate::state_file;
+
+/// Default number of `airdrop-batch` transactions awaiting confirmation at once.
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 4;
+
+/// Estimated compute units of creating an associated token account.
+const CREATE_ACCOUNT_COMPUTE_UNITS: u32 = 40_000;
+
+/// Estimated compute units of minting through the faucet program.
+const MINT_TOKENS_COMPUTE_UNITS: u32 = 20_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchRow {
+    /// Line of the row in the recipients file.
+    pub line: u64,
+    pub wallet: Pubkey,
+    /// Amount as written, in UI or base units.
+    pub amount: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BatchStatus {
+    Ok,
+    Failed,
+    /// Sent in an earlier run, or a duplicate of an earlier row.
+    Skipped,
+    /// Only simulated on a dry run.
+    Simulated,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchResult {
+    pub line: u64,
+    pub wallet: String,
+    pub amount: String,
+    pub status: BatchStatus,
+    pub signature: Option<String>,
+    pub error: Option<String>,
+}
+
+impl BatchResult {
+    pub fn new(row: &BatchRow, status: BatchStatus) -> Self {
+        Self {
+            line: row.line,
+            wallet: row.wallet.to_string(),
+            amount: row.amount.clone(),
+            status,
+            signature: None,
+            error: None,
+        }
+    }
+}
+
+impl fmt::Display for BatchStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ok => write!(f, "ok"),
+            Self::Failed => write!(f, "failed"),
+            Self::Skipped => write!(f, "skipped"),
+            Self::Simulated => write!(f, "simulated"),
+        }
+    }
+}
+
+/// Reads the recipients file at `path`.
+pub fn read_recipients(path: &Path) -> Result<Vec<BatchRow>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .trim(csv::Trim::All)
+        .from_path(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+
+    let mut rows = vec![];
+    for (i, record) in reader.records().enumerate() {
+        let record = record.with_context(|| format!("failed to read {}", path.display()))?;
+        let line = record
+            .position()
+            .map_or(i as u64 + 1, |position| position.line());
+        if record.len() != 2 {
+            bail!(
+                "{}:{}: expected a wallet and an amount",
+                path.display(),
+                line
+            );
+        }
+
+        let wallet = match Pubkey::from_str(&record[0]) {
+            Ok(wallet) => wallet,
+            // A header.
+            Err(_) if rows.is_empty() && i == 0 => continue,
+            Err(err) => bail!(
+                "{}:{}: invalid wallet {}: {}",
+                path.display(),
+                line,
+                &record[0],
+                err
+            ),
+        };
+        rows.push(BatchRow {
+            line,
+            wallet,
+            amount: record[1].to_string(),
+        });
+    }
+    Ok(rows)
+}
+
+/// Default path of the results of the recipients file at `path`, `<name>.results.csv` next
+/// to it.
+pub fn results_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{}.results.csv", stem))
+}
+
+/// Reads the results file at `path`, empty if it does not exist yet.
+pub fn read_results(path: &Path) -> Result<Vec<BatchResult>> {
+    let Some(contents) = state_file::read(path)? else {
+        return Ok(vec![]);
+    };
+
+    csv::Reader::from_reader(contents.as_bytes())
+        .deserialize()
+        .collect::<Result<_, _>>()
+        .with_context(|| format!("failed to parse {}", path.display()))
+}
+
+pub fn write_results(path: &Path, results: &[BatchResult]) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    for result in results {
+        writer.serialize(result)?;
+    }
+    state_file::write(path, writer.into_inner()?)
+}
+
+/// Splits `rows` into those left to airdrop and the results of those skipped, either because
+/// `previous` results hold the signature of an airdrop of the same wallet and amount or
+/// because an earlier row has the same wallet and amount.
+pub fn pending_rows(
+    rows: Vec<BatchRow>,
+    previous: &[BatchResult],
+) -> (Vec<BatchRow>, Vec<BatchResult>) {
+    let mut seen = HashSet::new();
+    let mut pending = vec![];
+    let mut skipped = vec![];
+    for row in rows {
+        let wallet = row.wallet.to_string();
+        let succeeded = previous.iter().find(|result| {
+            matches!(result.status, BatchStatus::Ok | BatchStatus::Skipped)
+                && result.signature.is_some()
+                && result.wallet == wallet
+                && result.amount == row.amount
+        });
+
+        if let Some(previous) = succeeded {
+            skipped.push(BatchResult {
+                signature: previous.signature.clone(),
+                ..BatchResult::new(&row, BatchStatus::Skipped)
+            });
+        } else if !seen.insert((row.wallet, row.amount.clone())) {
+            skipped.push(BatchResult {
+                error: Some("duplicate of an earlier row".to_string()),
+                ..BatchResult::new(&row, BatchStatus::Skipped)
+            });
+        } else {
+            pending.push(row);
+        }
+    }
+    (pending, skipped)
+}
+
+/// Signature of a failed airdrop of the wallet and amount of `row` in `previous` results, whose
+/// transaction was sent and may have landed after failing to confirm.
+pub fn failed_signature(row: &BatchRow, previous: &[BatchResult]) -> Option<Signature> {
+    let wallet = row.wallet.to_string();
+    previous
+        .iter()
+        .filter(|result| {
+            result.status == BatchStatus::Failed
+                && result.wallet == wallet
+                && result.amount == row.amount
+        })
+        .find_map(|result| result.signature.as_deref()?.parse().ok())
+}
+
+/// Airdrops of consecutive rows sent in a single transaction.
+#[derive(Debug, Default)]
+pub struct PackedTx<'a> {
+    pub rows: Vec<&'a BatchRow>,
+    pub ixs: Vec<Instruction>,
+    /// Wallets whose associated token account the transaction creates.
+    pub created_accounts: HashSet<Pubkey>,
+    compute_units: u32,
+}
+
+impl<'a> PackedTx<'a> {
+    /// Adds the airdrop of `row`, preceded by `create_account_ix` unless the transaction
+    /// already creates the token account of the wallet.
+    ///
+    /// Returns `false`, leaving the transaction unchanged, if the airdrop would take it past
+    /// `compute_unit_limit` or past the packet size once `budget_ixs` are prepended and
+    /// `fee_payer` and the other signers sign it.
+    pub fn try_push(
+        &mut self,
+        row: &'a BatchRow,
+        create_account_ix: Option<Instruction>,
+        mint_tokens_ix: Instruction,
+        fee_payer: &Pubkey,
+        budget_ixs: &[Instruction],
+        compute_unit_limit: u32,
+    ) -> bool {
+        let create_account_ix =
+            create_account_ix.filter(|_| !self.created_accounts.contains(&row.wallet));
+
+        let mut compute_units = self.compute_units + MINT_TOKENS_COMPUTE_UNITS;
+        if create_account_ix.is_some() {
+            compute_units += CREATE_ACCOUNT_COMPUTE_UNITS;
+        }
+        if !self.rows.is_empty() && compute_units > compute_unit_limit {
+            return false;
+        }
+
+        let mut ixs = budget_ixs.to_vec();
+        ixs.extend(self.ixs.iter().cloned());
+        ixs.extend(create_account_ix.iter().cloned());
+        ixs.push(mint_tokens_ix.clone());
+        // Signatures, prefixed with their compact length.
+        let message = Message::new(&ixs, Some(fee_payer));
+        let signatures = usize::from(message.header.num_required_signatures);
+        let size = 1 + signatures * SIGNATURE_BYTES + message.serialize().len();
+        if !self.rows.is_empty() && size > PACKET_DATA_SIZE {
+            return false;
+        }
+
+        if let Some(create_account_ix) = create_account_ix {
+            self.ixs.push(create_account_ix);
+            self.created_accounts.insert(row.wallet);
+        }
+        self.ixs.push(mint_tokens_ix);
+        self.rows.push(row);
+        self.compute_units = compute_units;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use solana_sdk::instruction::AccountMeta;
+
+    use super::*;
+    use crate::compute_budget::MAX_COMPUTE_UNIT_LIMIT;
+
+    fn write_recipients(name: &str, contents: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("spl-faucet-{}-{}.csv", process::id(), name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn row(line: u64, wallet: Pubkey, amount: &str) -> BatchRow {
+        BatchRow {
+            line,
+            wallet,
+            amount: amount.to_string(),
+        }
+    }
+
+    fn result(row: &BatchRow, status: BatchStatus, signature: Option<Signature>) -> BatchResult {
+        BatchResult {
+            signature: signature.map(|signature| signature.to_string()),
+            ..BatchResult::new(row, status)
+        }
+    }
+
+    #[test]
+    fn reads_recipients_with_a_header() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let path = write_recipients("header", &format!("wallet,amount\n{}, 1.5\n{},2\n", a, b));
+        let rows = read_recipients(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(rows, vec![row(2, a, "1.5"), row(3, b, "2")]);
+    }
+
+    #[test]
+    fn reads_recipients_without_a_header() {
+        let a = Pubkey::new_unique();
+        let path = write_recipients("no-header", &format!("{},1\n", a));
+        let rows = read_recipients(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(rows, vec![row(1, a, "1")]);
+    }
+
+    #[test]
+    fn rejects_invalid_recipients() {
+        let a = Pubkey::new_unique();
+        for (name, contents) in [
+            ("late-header", format!("{},1\nwallet,amount\n", a)),
+            (
+                "two-headers",
+                format!("wallet,amount\nwallet,amount\n{},1\n", a),
+            ),
+            ("missing-amount", format!("{}\n", a)),
+            ("extra-column", format!("{},1,2\n", a)),
+        ] {
+            let path = write_recipients(name, &contents);
+            let result = read_recipients(&path);
+            fs::remove_file(&path).unwrap();
+
+            assert!(result.is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn skips_airdropped_and_duplicate_rows() {
+        let (a, b, c) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let signature = Signature::new_unique();
+        let rows = vec![
+            row(1, a, "1"),
+            row(2, b, "1"),
+            row(3, b, "1"),
+            row(4, c, "1"),
+            row(5, a, "2"),
+        ];
+        let previous = vec![
+            result(&rows[0], BatchStatus::Ok, Some(signature)),
+            result(&rows[3], BatchStatus::Failed, Some(Signature::new_unique())),
+        ];
+
+        let (pending, skipped) = pending_rows(rows.clone(), &previous);
+
+        assert_eq!(
+            pending,
+            vec![rows[1].clone(), rows[3].clone(), rows[4].clone()]
+        );
+        assert_eq!(
+            skipped
+                .iter()
+                .map(|result| (result.line, result.status, result.signature.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, BatchStatus::Skipped, Some(signature.to_string())),
+                (3, BatchStatus::Skipped, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_the_signature_of_failed_rows() {
+        let signature = Signature::new_unique();
+        let failed = row(1, Pubkey::new_unique(), "1");
+        let unsent = row(2, Pubkey::new_unique(), "1");
+        let succeeded = row(3, Pubkey::new_unique(), "1");
+        let previous = vec![
+            result(&failed, BatchStatus::Failed, Some(signature)),
+            result(&unsent, BatchStatus::Failed, None),
+            result(&succeeded, BatchStatus::Ok, Some(Signature::new_unique())),
+        ];
+
+        assert_eq!(failed_signature(&failed, &previous), Some(signature));
+        assert_eq!(failed_signature(&unsent, &previous), None);
+        assert_eq!(failed_signature(&succeeded, &previous), None);
+        assert_eq!(
+            failed_signature(&row(4, failed.wallet, "2"), &previous),
+            None
+        );
+    }
+
+    fn mint_tokens_ix(wallet: &Pubkey) -> Instruction {
+        Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[0; 9],
+            vec![AccountMeta::new(*wallet, false)],
+        )
+    }
+
+    fn create_account_ix(wallet: &Pubkey) -> Instruction {
+        Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[1],
+            vec![AccountMeta::new(*wallet, false)],
+        )
+    }
+
+    #[test]
+    fn packs_rows_up_to_the_compute_unit_limit() {
+        let fee_payer = Pubkey::new_unique();
+        let rows: Vec<BatchRow> = (0..3)
+            .map(|line| row(line, Pubkey::new_unique(), "1"))
+            .collect();
+        let mut tx = PackedTx::default();
+
+        for row in &rows[..2] {
+            assert!(tx.try_push(
+                row,
+                None,
+                mint_tokens_ix(&row.wallet),
+                &fee_payer,
+                &[],
+                50_000
+            ));
+        }
+        assert!(!tx.try_push(
+            &rows[2],
+            None,
+            mint_tokens_ix(&rows[2].wallet),
+            &fee_payer,
+            &[],
+            50_000
+        ));
+        assert_eq!(tx.rows.len(), 2);
+        assert_eq!(tx.ixs.len(), 2);
+    }
+
+    #[test]
+    fn always_packs_the_first_row() {
+        let fee_payer = Pubkey::new_unique();
+        let row = row(1, Pubkey::new_unique(), "1");
+        let mut tx = PackedTx::default();
+
+        assert!(tx.try_push(
+            &row,
+            Some(create_account_ix(&row.wallet)),
+            mint_tokens_ix(&row.wallet),
+            &fee_payer,
+            &[],
+            0
+        ));
+    }
+
+    #[test]
+    fn creates_each_token_account_once() {
+        let fee_payer = Pubkey::new_unique();
+        let wallet = Pubkey::new_unique();
+        let rows = [row(1, wallet, "1"), row(2, wallet, "2")];
+        let mut tx = PackedTx::default();
+
+        for row in &rows {
+            assert!(tx.try_push(
+                row,
+                Some(create_account_ix(&wallet)),
+                mint_tokens_ix(&wallet),
+                &fee_payer,
+                &[],
+                MAX_COMPUTE_UNIT_LIMIT
+            ));
+        }
+        assert_eq!(tx.ixs.len(), 3);
+        assert_eq!(tx.created_accounts, HashSet::from([wallet]));
+    }
+
+    #[test]
+    fn packs_rows_up_to_the_packet_size() {
+        let fee_payer = Pubkey::new_unique();
+        let rows: Vec<BatchRow> = (0..100)
+            .map(|line| row(line, Pubkey::new_unique(), "1"))
+            .collect();
+        let mut tx = PackedTx::default();
+
+        let packed = rows
+            .iter()
+            .take_while(|row| {
+                tx.try_push(
+                    row,
+                    None,
+                    mint_tokens_ix(&row.wallet),
+                    &fee_payer,
+                    &[],
+                    u32::MAX,
+                )
+            })
+            .count();
+
+        assert!(packed > 1 && packed < rows.len());
+        assert_eq!(tx.rows.len(), packed);
+        let message = Message::new(&tx.ixs, Some(&fee_payer));
+        assert!(SIGNATURE_BYTES + 1 + message.serialize().len() <= PACKET_DATA_SIZE);
+    }
+}
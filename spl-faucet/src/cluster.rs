@@ -0,0 +1,196 @@
+//! Cluster detection from the RPC genesis hash.
+
+use std::fmt;
+
+use anyhow::Result;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::hash::Hash;
+
+const MAINNET_BETA_GENESIS_HASH: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+const DEVNET_GENESIS_HASH: &str = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
+const TESTNET_GENESIS_HASH: &str = "4uhcVJyU9pJkvQyS88uRDiswHXSCkY3zQawwpjk2NsNY";
+
+const MAINNET_BETA_URL: &str = "https://api.mainnet-beta.solana.com";
+const DEVNET_URL: &str = "https://api.devnet.solana.com";
+const TESTNET_URL: &str = "https://api.testnet.solana.com";
+const LOCALHOST_URL: &str = "http://localhost:8899";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cluster {
+    MainnetBeta,
+    Devnet,
+    Testnet,
+    /// Localnet or any other deployment, identified by its normalized RPC URL since its
+    /// genesis hash changes whenever it is reset.
+    Other {
+        url: String,
+        genesis_hash: Hash,
+    },
+}
+
+impl Cluster {
+    /// Identifies the cluster `rpc` is connected to.
+    pub async fn detect(rpc: &RpcClient) -> Result<Self> {
+        Ok(Self::new(rpc.get_genesis_hash().await?, &rpc.url()))
+    }
+
+    /// Cluster with `genesis_hash`, reached at `url`.
+    pub fn new(genesis_hash: Hash, url: &str) -> Self {
+        match genesis_hash.to_string().as_str() {
+            MAINNET_BETA_GENESIS_HASH => Self::MainnetBeta,
+            DEVNET_GENESIS_HASH => Self::Devnet,
+            TESTNET_GENESIS_HASH => Self::Testnet,
+            _ => Self::Other {
+                url: normalize_url(url),
+                genesis_hash,
+            },
+        }
+    }
+
+    pub fn is_mainnet(&self) -> bool {
+        *self == Self::MainnetBeta
+    }
+
+    /// Stable identifier of the cluster, its moniker for public clusters and its normalized
+    /// RPC URL otherwise.
+    pub fn key(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Registry key for a cluster given by the user, its moniker or its RPC URL, normalized the way
+/// [`Cluster::key`] does. The Solana CLI monikers and the public RPC URLs of mainnet-beta,
+/// devnet and testnet map to their moniker, `localhost` to the local validator URL.
+pub fn key_from_str(cluster: &str) -> String {
+    let cluster = cluster.trim();
+    let url = match cluster {
+        "m" | "mainnet-beta" => return Cluster::MainnetBeta.key(),
+        "d" | "devnet" => return Cluster::Devnet.key(),
+        "t" | "testnet" => return Cluster::Testnet.key(),
+        "l" | "localhost" => LOCALHOST_URL,
+        url if url.contains("://") => url,
+        moniker => return moniker.to_string(),
+    };
+
+    let url = normalize_url(url);
+    match url.as_str() {
+        MAINNET_BETA_URL => Cluster::MainnetBeta.key(),
+        DEVNET_URL => Cluster::Devnet.key(),
+        TESTNET_URL => Cluster::Testnet.key(),
+        _ => url,
+    }
+}
+
+impl fmt::Display for Cluster {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MainnetBeta => write!(f, "mainnet-beta"),
+            Self::Devnet => write!(f, "devnet"),
+            Self::Testnet => write!(f, "testnet"),
+            Self::Other { url, .. } => write!(f, "{}", url),
+        }
+    }
+}
+
+/// `url` with a lowercase scheme and host, `localhost` written `127.0.0.1`, and without its
+/// query, which may hold an API key, or trailing slashes.
+fn normalize_url(url: &str) -> String {
+    let url = url.trim();
+    let url = url.split(['?', '#']).next().unwrap_or_default();
+    let (scheme, rest) = url.split_once("://").unwrap_or(("http", url));
+    let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+
+    let host = host.to_lowercase();
+    let host = match host.strip_prefix("localhost") {
+        Some(port) if port.is_empty() || port.starts_with(':') => format!("127.0.0.1{}", port),
+        _ => host,
+    };
+    format!(
+        "{}://{}{}",
+        scheme.to_lowercase(),
+        host,
+        path.trim_end_matches('/')
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn normalizes_scheme_and_host_case() {
+        assert_eq!(
+            key_from_str("HTTPS://RPC.Example.COM/Path"),
+            "https://rpc.example.com/Path"
+        );
+    }
+
+    #[test]
+    fn writes_localhost_as_127_0_0_1() {
+        assert_eq!(key_from_str("http://localhost"), "http://127.0.0.1");
+        assert_eq!(
+            key_from_str("http://LOCALHOST:8899"),
+            "http://127.0.0.1:8899"
+        );
+        assert_eq!(key_from_str("localhost"), "http://127.0.0.1:8899");
+        assert_eq!(
+            key_from_str("http://localhostname:8899"),
+            "http://localhostname:8899"
+        );
+    }
+
+    #[test]
+    fn strips_query_fragment_and_trailing_slashes() {
+        assert_eq!(
+            key_from_str("https://rpc.example.com/?api-key=secret"),
+            "https://rpc.example.com"
+        );
+        assert_eq!(
+            key_from_str("https://rpc.example.com/rpc#fragment"),
+            "https://rpc.example.com/rpc"
+        );
+        assert_eq!(
+            key_from_str(" https://rpc.example.com/rpc// "),
+            "https://rpc.example.com/rpc"
+        );
+    }
+
+    #[test]
+    fn defaults_to_http_without_a_scheme() {
+        assert_eq!(normalize_url("127.0.0.1:8899/"), "http://127.0.0.1:8899");
+        assert_eq!(normalize_url("localhost:8899"), "http://127.0.0.1:8899");
+    }
+
+    #[test]
+    fn keys_public_clusters_by_moniker() {
+        for (cluster, key) in [
+            ("m", "mainnet-beta"),
+            ("https://api.mainnet-beta.solana.com/", "mainnet-beta"),
+            ("d", "devnet"),
+            ("https://API.devnet.solana.com", "devnet"),
+            ("t", "testnet"),
+            ("testnet", "testnet"),
+        ] {
+            assert_eq!(key_from_str(cluster), key, "{}", cluster);
+        }
+        assert_eq!(key_from_str(" staging "), "staging");
+    }
+
+    #[test]
+    fn keys_other_clusters_by_url() {
+        let genesis_hash = Hash::new_unique();
+
+        let cluster = Cluster::new(genesis_hash, "http://localhost:8899/");
+        assert_eq!(cluster.key(), "http://127.0.0.1:8899");
+        assert_eq!(cluster.key(), key_from_str("localhost"));
+        assert!(!cluster.is_mainnet());
+
+        let cluster = Cluster::new(
+            Hash::from_str(DEVNET_GENESIS_HASH).unwrap(),
+            "https://rpc.example.com",
+        );
+        assert_eq!(cluster, Cluster::Devnet);
+    }
+}
@@ -0,0 +1,101 @@
+//! ComputeBudget instructions and priority fee estimation.
+
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use anyhow::{bail, Result};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction, instruction::Instruction, pubkey::Pubkey,
+};
+
+/// Compute unit limit a transaction may request.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Maximum number of accounts `getRecentPrioritizationFees` accepts per request.
+const MAX_PRIORITIZATION_FEE_ACCOUNTS: usize = 128;
+
+/// A compute budget value, either given explicitly or estimated from the cluster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetSetting<T> {
+    Fixed(T),
+    Auto,
+}
+
+impl<T: FromStr> FromStr for BudgetSetting<T> {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            _ => match s.parse() {
+                Ok(value) => Ok(Self::Fixed(value)),
+                Err(_) => bail!("invalid value {}, expected a number or `auto`", s),
+            },
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for BudgetSetting<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fixed(value) => write!(f, "{}", value),
+            Self::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+/// Builds the ComputeBudget instructions to prepend to a transaction.
+pub fn compute_budget_ixs(
+    compute_unit_limit: Option<u32>,
+    priority_fee: Option<u64>,
+) -> Vec<Instruction> {
+    let mut ixs = vec![];
+
+    if let Some(compute_unit_limit) = compute_unit_limit {
+        ixs.push(ComputeBudgetInstruction::set_compute_unit_limit(
+            compute_unit_limit,
+        ));
+    }
+    if let Some(priority_fee) = priority_fee {
+        ixs.push(ComputeBudgetInstruction::set_compute_unit_price(
+            priority_fee,
+        ));
+    }
+
+    ixs
+}
+
+/// Compute unit limit covering `units_consumed` with a 10% margin.
+pub fn compute_unit_limit_with_margin(units_consumed: u64) -> u32 {
+    let limit = units_consumed.saturating_add(units_consumed / 10);
+    limit.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32
+}
+
+/// Priority fee in micro-lamports per compute unit, the 75th percentile of the fees paid in
+/// recent slots by transactions locking any of `writable_accounts`.
+///
+/// Accounts are queried in chunks of the RPC limit, keeping the highest fee of each slot.
+pub async fn recent_priority_fee(rpc: &RpcClient, writable_accounts: &[Pubkey]) -> Result<u64> {
+    let mut slot_fees = HashMap::new();
+    let chunks: Vec<&[Pubkey]> = match writable_accounts {
+        [] => vec![&[]],
+        _ => writable_accounts
+            .chunks(MAX_PRIORITIZATION_FEE_ACCOUNTS)
+            .collect(),
+    };
+    for accounts in chunks {
+        for fee in rpc.get_recent_prioritization_fees(accounts).await? {
+            let slot_fee = slot_fees.entry(fee.slot).or_default();
+            *slot_fee = fee.prioritization_fee.max(*slot_fee);
+        }
+    }
+
+    let mut fees: Vec<u64> = slot_fees.into_values().collect();
+
+    if fees.is_empty() {
+        return Ok(0);
+    }
+
+    fees.sort_unstable();
+    Ok(fees[(fees.len() - 1) * 3 / 4])
+}
@@ -0,0 +1,228 @@
+//! `spl-faucet` settings file, `~/.config/spl-faucet/config.toml` by default, and the
+//! connection settings shared with the Solana CLI config.
+//!
+//! ```toml
+//! # used for any cluster without its own entry
+//! program_id = "4bXpkKSV8swHSnwqtzuboGPaPDeEgAn4Vt8GfarV5rZt"
+//!
+//! [program_ids]
+//! devnet = "..."
+//! "http://localhost:8899" = "..."
+//! ```
+
+use std::{collections::HashMap, path::Path, str::FromStr};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use solana_cli_config::ConfigInput;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+
+use crate::{cluster, state_file};
+
+pub const DEFAULT_CONFIG_PATH: &str = "~/.config/spl-faucet/config.toml";
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    /// Faucet program ID for clusters missing from `program_ids`.
+    pub program_id: Option<String>,
+    /// Faucet program IDs keyed by cluster moniker or RPC URL.
+    #[serde(default)]
+    pub program_ids: HashMap<String, String>,
+}
+
+impl Config {
+    /// Reads the config at `path`, falling back to an empty config if the file does not exist.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        state_file::load_toml(path.as_ref())
+    }
+
+    /// Faucet program ID configured for the cluster at `url`, if any. Clusters are compared by
+    /// their [`cluster::key_from_str`] key, so an entry may be a moniker such as `devnet` or any
+    /// spelling of the RPC URL, and entries for the same cluster must agree.
+    pub fn program_id(&self, url: &str) -> Result<Option<Pubkey>> {
+        let key = cluster::key_from_str(url);
+        let mut matching = self
+            .program_ids
+            .iter()
+            .filter(|(cluster, _)| cluster::key_from_str(cluster) == key)
+            .collect::<Vec<_>>();
+        matching.sort();
+        if let Some((first, program_id)) = matching.first() {
+            if let Some((other, _)) = matching.iter().find(|(_, other)| other != program_id) {
+                bail!(
+                    "program_ids entries {} and {} for {} differ, keep one of them",
+                    first,
+                    other,
+                    key
+                );
+            }
+        }
+
+        matching
+            .first()
+            .map(|(_, program_id)| *program_id)
+            .or(self.program_id.as_ref())
+            .map(|program_id| {
+                Pubkey::from_str(program_id)
+                    .with_context(|| format!("invalid program ID {}", program_id))
+            })
+            .transpose()
+    }
+}
+
+/// RPC URL, keypair path and commitment, from command line flags or the Solana CLI config.
+#[derive(Debug)]
+pub struct ClusterConfig {
+    pub json_rpc_url: String,
+    pub keypair_path: String,
+    pub commitment: CommitmentConfig,
+}
+
+impl ClusterConfig {
+    /// Resolves each setting from its flag, then the Solana CLI config at `config_file`
+    /// (`~/.config/solana/cli/config.yml` by default), then the Solana defaults.
+    ///
+    /// `url` may be a moniker such as `localhost`, `devnet`, `testnet` or `mainnet-beta`.
+    pub fn resolve(
+        url: Option<&str>,
+        keypair_path: Option<&str>,
+        commitment: Option<&str>,
+        config_file: Option<&str>,
+    ) -> Result<Self> {
+        let solana_config = match config_file {
+            Some(config_file) => solana_cli_config::Config::load(config_file)
+                .with_context(|| format!("failed to read Solana config {}", config_file))?,
+            None => solana_cli_config::CONFIG_FILE
+                .as_ref()
+                .and_then(|config_file| solana_cli_config::Config::load(config_file).ok())
+                .unwrap_or_default(),
+        };
+
+        let (_, json_rpc_url) = ConfigInput::compute_json_rpc_url_setting(
+            url.unwrap_or_default(),
+            &solana_config.json_rpc_url,
+        );
+        let (_, keypair_path) = ConfigInput::compute_keypair_path_setting(
+            keypair_path.unwrap_or_default(),
+            &solana_config.keypair_path,
+        );
+        let (_, commitment) = ConfigInput::compute_commitment_config(
+            commitment.unwrap_or_default(),
+            &solana_config.commitment,
+        );
+
+        Ok(Self {
+            json_rpc_url,
+            keypair_path: shellexpand::tilde(&keypair_path).to_string(),
+            commitment,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCALNET_PROGRAM_ID: &str = "Fau1111111111111111111111111111111111111111";
+    const DEFAULT_PROGRAM_ID: &str = "4bXpkKSV8swHSnwqtzuboGPaPDeEgAn4Vt8GfarV5rZt";
+
+    fn config(toml: &str) -> Config {
+        toml::from_str(toml).unwrap()
+    }
+
+    fn program_id(program_id: &str) -> Option<Pubkey> {
+        Some(Pubkey::from_str(program_id).unwrap())
+    }
+
+    #[test]
+    fn matches_program_ids_by_url() {
+        let config = config(&format!(
+            "[program_ids]\n\"http://localhost:8899/\" = \"{}\"",
+            LOCALNET_PROGRAM_ID
+        ));
+
+        for url in ["http://localhost:8899", "http://localhost:8899/"] {
+            assert_eq!(
+                config.program_id(url).unwrap(),
+                program_id(LOCALNET_PROGRAM_ID),
+                "{}",
+                url
+            );
+        }
+        assert_eq!(config.program_id("http://localhost:8900").unwrap(), None);
+    }
+
+    #[test]
+    fn matches_program_ids_by_cluster_key() {
+        let config = config(&format!(
+            "[program_ids]\ndevnet = \"{}\"\n\"HTTP://LOCALHOST:8899/?api-key=1\" = \"{}\"",
+            DEFAULT_PROGRAM_ID, LOCALNET_PROGRAM_ID
+        ));
+
+        for url in [
+            "https://api.devnet.solana.com",
+            "https://api.devnet.solana.com/",
+            "d",
+        ] {
+            assert_eq!(
+                config.program_id(url).unwrap(),
+                program_id(DEFAULT_PROGRAM_ID),
+                "{}",
+                url
+            );
+        }
+        for url in ["http://127.0.0.1:8899", "localhost"] {
+            assert_eq!(
+                config.program_id(url).unwrap(),
+                program_id(LOCALNET_PROGRAM_ID),
+                "{}",
+                url
+            );
+        }
+        assert_eq!(config.program_id("testnet").unwrap(), None);
+    }
+
+    #[test]
+    fn rejects_differing_program_ids_for_one_cluster() {
+        let config = config(&format!(
+            "[program_ids]\n\"http://localhost:8899\" = \"{}\"\n\
+             \"http://localhost:8899/\" = \"{}\"",
+            DEFAULT_PROGRAM_ID, LOCALNET_PROGRAM_ID
+        ));
+
+        assert!(config.program_id("http://localhost:8899").is_err());
+        assert_eq!(config.program_id("http://localhost:8900").unwrap(), None);
+    }
+
+    #[test]
+    fn falls_back_to_the_default_program_id() {
+        let config = config(&format!(
+            "program_id = \"{}\"\n[program_ids]\n\"http://localhost:8899\" = \"{}\"",
+            DEFAULT_PROGRAM_ID, LOCALNET_PROGRAM_ID
+        ));
+
+        assert_eq!(
+            config.program_id("http://localhost:8899").unwrap(),
+            program_id(LOCALNET_PROGRAM_ID)
+        );
+        assert_eq!(
+            config.program_id("https://api.devnet.solana.com").unwrap(),
+            program_id(DEFAULT_PROGRAM_ID)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_program_ids() {
+        let config = config("[program_ids]\n\"http://localhost:8899\" = \"faucet\"");
+
+        assert!(config.program_id("http://localhost:8899").is_err());
+        assert_eq!(config.program_id("http://localhost:8900").unwrap(), None);
+    }
+
+    #[test]
+    fn defaults_without_a_config_file() {
+        let config = Config::load("/nonexistent/spl-faucet/config.toml").unwrap();
+
+        assert_eq!(config.program_id("http://localhost:8899").unwrap(), None);
+    }
+}
@@ -0,0 +1,343 @@
+//! Errors of `spl-faucet`, each kind exiting with its own code so scripts can tell an
+//! unreachable RPC node from a failed transaction or a bad argument.
+//!
+//! Most of the crate returns [`anyhow::Result`], with an [`Error`] inside where the kind is
+//! known. [`Error::from`] classifies the rest, falling back to [`Error::InvalidInput`].
+
+use std::fmt;
+
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_request::{RpcError, RpcResponseErrorData},
+};
+use solana_sdk::{
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    transaction::{Transaction, TransactionError},
+};
+
+#[derive(Debug)]
+pub enum Error {
+    /// Unreadable or invalid Solana CLI config, faucet config or registry.
+    Config(anyhow::Error),
+    /// Keypair or other signer that cannot be read or cannot sign.
+    Signer(anyhow::Error),
+    /// RPC node unreachable or failing a request.
+    Rpc(ClientError),
+    /// Transaction rejected or failed, with the program logs when the node returned them.
+    Transaction {
+        err: TransactionError,
+        /// Program called by the failed instruction, when known.
+        program: Option<Pubkey>,
+        logs: Vec<String>,
+    },
+    /// Faucet program failing an instruction with one of its custom errors.
+    FaucetProgram { code: u32, logs: Vec<String> },
+    /// Invalid argument or file, or on-chain state the command cannot proceed from.
+    InvalidInput(anyhow::Error),
+}
+
+impl Error {
+    /// Failure `err` of `tx`, a [`Self::FaucetProgram`] error if the failed instruction calls
+    /// the faucet program at `program_id`.
+    pub fn failed_transaction(
+        tx: &Transaction,
+        program_id: &Pubkey,
+        err: TransactionError,
+        logs: Vec<String>,
+    ) -> Self {
+        let program = match err {
+            TransactionError::InstructionError(index, _) => {
+                tx.message.program_id(usize::from(index)).copied()
+            }
+            _ => None,
+        };
+        match err {
+            TransactionError::InstructionError(_, InstructionError::Custom(code))
+                if program.as_ref() == Some(program_id) =>
+            {
+                Self::FaucetProgram { code, logs }
+            }
+            _ => Self::Transaction { err, program, logs },
+        }
+    }
+
+    /// Failure `err` of sending `tx`, a transaction error if the node ran it.
+    pub fn failed_send(tx: &Transaction, program_id: &Pubkey, err: ClientError) -> Self {
+        match err.get_transaction_error() {
+            Some(tx_err) => Self::failed_transaction(tx, program_id, tx_err, preflight_logs(&err)),
+            None => Self::from_client_error(err),
+        }
+    }
+
+    fn from_client_error(err: ClientError) -> Self {
+        match err.get_transaction_error() {
+            Some(tx_err) => Self::Transaction {
+                err: tx_err,
+                program: None,
+                logs: preflight_logs(&err),
+            },
+            None => match err.kind() {
+                ClientErrorKind::SigningError(_) => Self::Signer(err.into()),
+                _ => Self::Rpc(err),
+            },
+        }
+    }
+
+    /// Classifies `err` like [`Error::from`], along with its message, which keeps the context
+    /// added around the classified error.
+    pub fn with_message(err: anyhow::Error) -> (Self, String) {
+        let typed = err
+            .chain()
+            .position(|cause| cause.is::<Error>() || cause.is::<ClientError>());
+        match typed {
+            Some(typed) => {
+                let mut message: Vec<String> =
+                    err.chain().take(typed).map(ToString::to_string).collect();
+                let err = Self::from(err);
+                message.push(err.to_string());
+                (err, message.join(": "))
+            }
+            None => {
+                let message = format!("{:#}", err);
+                (Self::from(err), message)
+            }
+        }
+    }
+
+    /// Process exit code of the error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            // Same as clap usage errors.
+            Self::InvalidInput(_) => 2,
+            Self::Config(_) => 3,
+            Self::Signer(_) => 4,
+            Self::Rpc(_) => 5,
+            Self::Transaction { .. } => 6,
+            Self::FaucetProgram { .. } => 7,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Config(_) => "config",
+            Self::Signer(_) => "signer",
+            Self::Rpc(_) => "rpc",
+            Self::Transaction { .. } => "transaction",
+            Self::FaucetProgram { .. } => "faucet-program",
+            Self::InvalidInput(_) => "invalid-input",
+        }
+    }
+
+    /// Program logs of a failed transaction.
+    pub fn logs(&self) -> &[String] {
+        match self {
+            Self::Transaction { logs, .. } | Self::FaucetProgram { logs, .. } => logs,
+            _ => &[],
+        }
+    }
+}
+
+impl From<anyhow::Error> for Error {
+    fn from(err: anyhow::Error) -> Self {
+        let err = match err.downcast::<Error>() {
+            Ok(err) => return err,
+            Err(err) => err,
+        };
+        match err.downcast::<ClientError>() {
+            Ok(err) => Self::from_client_error(err),
+            Err(err) => Self::InvalidInput(err),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Config(err) | Self::Signer(err) | Self::InvalidInput(err) => {
+                write!(f, "{:#}", err)
+            }
+            Self::Rpc(err) => write!(f, "RPC request failed: {}", err),
+            Self::Transaction { err, .. } => write!(f, "transaction failed: {}", err),
+            Self::FaucetProgram { code, .. } => {
+                write!(f, "faucet program failed with error {} ({:#x})", code, code)
+            }
+        }
+    }
+}
+
+// The display already includes the wrapped errors, so there is no source.
+impl std::error::Error for Error {}
+
+/// Logs of the preflight simulation that rejected a transaction.
+fn preflight_logs(err: &ClientError) -> Vec<String> {
+    match err.kind() {
+        ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(result),
+            ..
+        }) => result.logs.clone().unwrap_or_default(),
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, io};
+
+    use anyhow::{anyhow, Context};
+    use solana_client::rpc_response::RpcSimulateTransactionResult;
+    use solana_sdk::{instruction::Instruction, signer::SignerError};
+
+    use super::*;
+
+    fn tx(program_id: &Pubkey) -> Transaction {
+        Transaction::new_with_payer(
+            &[Instruction::new_with_bytes(*program_id, &[], vec![])],
+            Some(&Pubkey::new_unique()),
+        )
+    }
+
+    fn custom_error(code: u32) -> TransactionError {
+        TransactionError::InstructionError(0, InstructionError::Custom(code))
+    }
+
+    fn preflight_failure(err: TransactionError, logs: &[&str]) -> ClientError {
+        ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            code: -32002,
+            message: "Transaction simulation failed".to_string(),
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(
+                RpcSimulateTransactionResult {
+                    err: Some(err),
+                    logs: Some(logs.iter().map(ToString::to_string).collect()),
+                    accounts: None,
+                    units_consumed: None,
+                    return_data: None,
+                },
+            ),
+        })
+        .into()
+    }
+
+    fn io_error() -> ClientError {
+        ClientErrorKind::Io(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            "connection refused",
+        ))
+        .into()
+    }
+
+    #[test]
+    fn classifies_faucet_program_errors() {
+        let program_id = Pubkey::new_unique();
+
+        let err = Error::failed_transaction(&tx(&program_id), &program_id, custom_error(1), vec![]);
+        assert!(matches!(err, Error::FaucetProgram { code: 1, .. }));
+        assert_eq!(err.exit_code(), 7);
+
+        let err = Error::failed_transaction(
+            &tx(&Pubkey::new_unique()),
+            &program_id,
+            custom_error(1),
+            vec![],
+        );
+        assert!(matches!(err, Error::Transaction { .. }));
+
+        let err = Error::failed_transaction(
+            &tx(&program_id),
+            &program_id,
+            TransactionError::InstructionError(0, InstructionError::IncorrectProgramId),
+            vec![],
+        );
+        assert!(matches!(err, Error::Transaction { program: Some(p), .. } if p == program_id));
+        assert_eq!(err.exit_code(), 6);
+    }
+
+    #[test]
+    fn classifies_failed_sends() {
+        let program_id = Pubkey::new_unique();
+
+        let err = Error::failed_send(
+            &tx(&program_id),
+            &program_id,
+            preflight_failure(custom_error(2), &["Program log: Error"]),
+        );
+        assert!(matches!(err, Error::FaucetProgram { code: 2, .. }));
+        assert_eq!(err.logs(), ["Program log: Error"]);
+
+        let err = Error::failed_send(&tx(&program_id), &program_id, io_error());
+        assert!(matches!(err, Error::Rpc(_)));
+        assert_eq!(err.exit_code(), 5);
+
+        let err = Error::failed_send(
+            &tx(&program_id),
+            &program_id,
+            ClientErrorKind::SigningError(SignerError::NotEnoughSigners).into(),
+        );
+        assert!(matches!(err, Error::Signer(_)));
+        assert_eq!(err.exit_code(), 4);
+    }
+
+    #[test]
+    fn classifies_anyhow_errors() {
+        let err = Error::from(anyhow::Error::from(Error::Config(anyhow!("bad config"))));
+        assert!(matches!(err, Error::Config(_)));
+
+        let err = Error::from(anyhow::Error::from(io_error()).context("failed to fetch"));
+        assert!(matches!(err, Error::Rpc(_)));
+
+        let err = Error::from(anyhow::Error::from(preflight_failure(
+            TransactionError::AccountNotFound,
+            &[],
+        )));
+        assert!(matches!(err, Error::Transaction { .. }));
+
+        let err = Error::from(anyhow!("invalid amount"));
+        assert!(matches!(err, Error::InvalidInput(_)));
+        assert_eq!(err.exit_code(), 2);
+    }
+
+    #[test]
+    fn keeps_context_around_typed_errors() {
+        let err = Err::<(), _>(Error::Signer(anyhow!("bad keypair")))
+            .context("failed to create the USDC faucet")
+            .unwrap_err();
+        let (err, message) = Error::with_message(err);
+        assert!(matches!(err, Error::Signer(_)));
+        assert_eq!(message, "failed to create the USDC faucet: bad keypair");
+
+        let err = anyhow::Error::from(io_error()).context("failed to fetch");
+        let (err, message) = Error::with_message(err);
+        assert!(matches!(err, Error::Rpc(_)));
+        assert_eq!(
+            message,
+            "failed to fetch: RPC request failed: connection refused"
+        );
+
+        let err = anyhow!("invalid amount").context("failed to parse");
+        let (err, message) = Error::with_message(err);
+        assert!(matches!(err, Error::InvalidInput(_)));
+        assert_eq!(message, "failed to parse: invalid amount");
+    }
+
+    #[test]
+    fn exit_codes_are_distinct() {
+        let errors = [
+            Error::InvalidInput(anyhow!("")),
+            Error::Config(anyhow!("")),
+            Error::Signer(anyhow!("")),
+            Error::Rpc(io_error()),
+            Error::Transaction {
+                err: TransactionError::AccountNotFound,
+                program: None,
+                logs: vec![],
+            },
+            Error::FaucetProgram {
+                code: 0,
+                logs: vec![],
+            },
+        ];
+        let codes: HashSet<i32> = errors.iter().map(Error::exit_code).collect();
+        assert_eq!(codes.len(), errors.len());
+        assert!(!codes.contains(&0) && !codes.contains(&1));
+    }
+}
@@ -0,0 +1,89 @@
+//! Instruction builders and PDA helpers for the spl-token-faucet program.
+
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    sysvar,
+};
+use spl_token_faucet::instruction::FaucetInstruction;
+
+/// PDA of the faucet program, the mint authority of every faucet mint.
+pub fn get_faucet_pda(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"faucet"], program_id)
+}
+
+/// Initializes `faucet_account` for `mint_account`, capping each airdrop at `amount` base units.
+///
+/// The mint authority must already be the faucet PDA.
+pub fn create_init_faucet_ix(
+    program_id: &Pubkey,
+    mint_account: Pubkey,
+    faucet_account: Pubkey,
+    admin: Option<Pubkey>,
+    amount: u64,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(mint_account, false),
+        AccountMeta::new(faucet_account, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ];
+
+    if let Some(admin) = admin {
+        accounts.push(AccountMeta::new_readonly(admin, false));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FaucetInstruction::InitFaucet { amount }.pack(),
+    }
+}
+
+/// Mints `amount` base units to `destination_account`.
+///
+/// `admin` must sign when `amount` exceeds the faucet limit.
+pub fn create_mint_tokens_ix(
+    program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    mint_account: Pubkey,
+    destination_account: Pubkey,
+    faucet_account: Pubkey,
+    admin: Option<Pubkey>,
+    amount: u64,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(get_faucet_pda(program_id).0, false),
+        AccountMeta::new(mint_account, false),
+        AccountMeta::new(destination_account, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(faucet_account, false),
+    ];
+
+    if let Some(admin) = admin {
+        accounts.push(AccountMeta::new_readonly(admin, true));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: FaucetInstruction::MintTokens { amount }.pack(),
+    }
+}
+
+/// Closes `faucet_account`, sending its rent to `destination_account`.
+pub fn create_close_faucet_ix(
+    program_id: &Pubkey,
+    admin: Pubkey,
+    destination_account: Pubkey,
+    faucet_account: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new(destination_account, false),
+            AccountMeta::new(faucet_account, false),
+        ],
+        data: FaucetInstruction::CloseFaucet.pack(),
+    }
+}
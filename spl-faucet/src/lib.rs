@@ -0,0 +1,1836 @@
+//! Client for the [spl-token-faucet](https://github.com/paul-schaaf/spl-token-faucet) program.
+//!
+//! [`FaucetClient`] wraps an [`RpcClient`] and a wallet and exposes the create, airdrop,
+//! close and inspect flows used by the `spl-faucet` CLI. The raw instruction builders
+//! live in [`instruction`].
+
+pub mod amount;
+pub mod batch;
+pub mod cluster;
+pub mod compute_budget;
+pub mod config;
+pub mod error;
+pub mod instruction;
+pub mod manifest;
+pub mod metadata;
+pub mod output;
+pub mod registry;
+pub mod signer;
+pub mod state_file;
+pub mod token;
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use futures::{stream, StreamExt};
+use mpl_token_metadata::accounts::Metadata;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    nonce_utils,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+    rpc_request::MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS,
+};
+use solana_sdk::{
+    account::Account,
+    hash::Hash,
+    instruction::{Instruction, InstructionError},
+    nonce::{self, state::Data as NonceData},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{write_keypair_file, Keypair, Signature},
+    signer::Signer,
+    system_instruction::{advance_nonce_account, create_account, create_nonce_account, transfer},
+    sysvar::rent::Rent,
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::{create_associated_token_account, create_associated_token_account_idempotent},
+};
+use spl_token::solana_program::{program_option::COption, pubkey};
+use spl_token_2022::{
+    extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+    instruction::AuthorityType,
+    state::Mint,
+};
+use spl_token_faucet::state::Faucet;
+use spl_token_metadata_interface::state::{Field, TokenMetadata as Token2022Metadata};
+use tokio::sync::OnceCell;
+
+use crate::{
+    amount::Amount,
+    batch::{BatchResult, BatchRow, BatchStatus, PackedTx},
+    cluster::Cluster,
+    compute_budget::{
+        compute_budget_ixs, compute_unit_limit_with_margin, recent_priority_fee, BudgetSetting,
+        MAX_COMPUTE_UNIT_LIMIT,
+    },
+    error::Error,
+    manifest::{Manifest, Resolved, ResolvedToken},
+    metadata::{
+        create_metaplex_metadata_ix, get_metaplex_metadata_address, update_metaplex_metadata_ix,
+        MetadataUpdate, TokenMetadata, METAPLEX_METADATA_LEN,
+    },
+    registry::{Registered, Registry, RegistryEntry},
+    token::{MintExtensions, TokenProgram},
+};
+
+pub use instruction::{
+    create_close_faucet_ix, create_init_faucet_ix, create_mint_tokens_ix, get_faucet_pda,
+};
+
+/// Default address of the faucet program, overridable per cluster.
+pub const FAUCET_PROGRAM_ID: Pubkey = pubkey!("4bXpkKSV8swHSnwqtzuboGPaPDeEgAn4Vt8GfarV5rZt");
+
+/// Offset of the `admin` option in a packed [`Faucet`], after `is_initialized`.
+pub const FAUCET_ADMIN_OFFSET: usize = 1;
+
+/// Offset of the mint in a packed [`Faucet`], after `is_initialized` and the `admin` option.
+pub const FAUCET_MINT_OFFSET: usize = 37;
+
+/// Maximum number of accounts `getMultipleAccounts` returns per request.
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Parameters of [`FaucetClient::create`].
+pub struct CreateOptions {
+    pub decimals: u8,
+    /// Airdrop limit.
+    pub max_amount: Amount,
+    /// Admin allowed to close the faucet and mint past the limit.
+    pub admin: Option<Pubkey>,
+    /// Token program owning the new mint.
+    pub token_program: TokenProgram,
+    /// Token-2022 extensions of the new mint.
+    pub extensions: MintExtensions,
+    /// Name, symbol and URI of the new mint, see [`metadata`].
+    pub metadata: Option<TokenMetadata>,
+    /// Signer of the new mint, a generated keypair if not set.
+    pub mint_keypair: Option<Box<dyn Signer>>,
+    /// Signer of the new faucet account, a generated keypair if not set.
+    pub faucet_keypair: Option<Box<dyn Signer>>,
+    /// Directory the generated keypairs are saved to before the transaction is sent.
+    pub out_dir: Option<PathBuf>,
+    /// Name of the faucet in the registry, the metadata symbol or else the faucet address if
+    /// not set.
+    pub alias: Option<String>,
+}
+
+impl CreateOptions {
+    pub fn new(decimals: u8, max_amount: Amount) -> Self {
+        Self {
+            decimals,
+            max_amount,
+            admin: None,
+            token_program: TokenProgram::default(),
+            extensions: MintExtensions::default(),
+            metadata: None,
+            mint_keypair: None,
+            faucet_keypair: None,
+            out_dir: None,
+            alias: None,
+        }
+    }
+}
+
+/// Result of [`FaucetClient::create`].
+#[derive(Debug)]
+pub struct CreatedFaucet {
+    pub mint: Pubkey,
+    pub faucet: Pubkey,
+    /// Faucet PDA, the mint authority.
+    pub pda: Pubkey,
+    pub admin: Option<Pubkey>,
+    pub token_program: TokenProgram,
+    pub extensions: MintExtensions,
+    pub metadata: Option<TokenMetadata>,
+    /// Metaplex metadata account, or the mint itself for Token-2022 mints.
+    pub metadata_account: Option<Pubkey>,
+    pub decimals: u8,
+    /// Airdrop limit in base units.
+    pub max_amount: u64,
+    /// Generated keypairs written to [`CreateOptions::out_dir`].
+    pub keypair_files: Vec<PathBuf>,
+    /// Alias the faucet was registered under, none on a dry run or without a registry.
+    pub alias: Option<String>,
+    pub sent: Sent,
+}
+
+/// Outcome for one token of [`FaucetClient::apply`].
+#[derive(Debug)]
+pub enum AppliedToken {
+    /// The faucet recorded in the resolved file exists and was left alone.
+    Existing {
+        symbol: String,
+        token: ResolvedToken,
+    },
+    Created {
+        symbol: String,
+        created: Box<CreatedFaucet>,
+    },
+}
+
+/// Result of [`FaucetClient::apply`].
+#[derive(Debug)]
+pub struct Applied {
+    pub tokens: Vec<AppliedToken>,
+    pub resolved_path: PathBuf,
+}
+
+/// Result of [`FaucetClient::airdrop`].
+#[derive(Debug)]
+pub struct Airdrop {
+    pub token_account: Pubkey,
+    /// Balance of `token_account` after the airdrop in UI units, unknown on a dry run.
+    pub balance: Option<String>,
+    pub sent: Sent,
+}
+
+/// Result of [`FaucetClient::airdrop_batch`].
+#[derive(Debug)]
+pub struct BatchAirdrop {
+    /// Results of every row of the recipients file, in file order.
+    pub results: Vec<BatchResult>,
+    pub results_path: PathBuf,
+}
+
+/// Result of [`FaucetClient::close`].
+#[derive(Debug)]
+pub struct ClosedFaucet {
+    pub faucet: Pubkey,
+    pub destination: Pubkey,
+    pub lamports: u64,
+    pub sent: Sent,
+}
+
+/// Result of [`FaucetClient::update_metadata`].
+#[derive(Debug)]
+pub struct UpdatedMetadata {
+    pub mint: Pubkey,
+    /// Metaplex metadata account, or the mint itself for Token-2022 mints.
+    pub metadata_account: Pubkey,
+    /// Metadata after the update.
+    pub metadata: TokenMetadata,
+    pub sent: Sent,
+}
+
+/// Result of [`FaucetClient::create_nonce`].
+#[derive(Debug)]
+pub struct CreatedNonce {
+    pub nonce: Pubkey,
+    pub authority: Pubkey,
+    /// Rent deposited into the nonce account.
+    pub lamports: u64,
+    pub sent: Sent,
+}
+
+/// Decoded on-chain state of a faucet and its mint, see [`FaucetClient::inspect`].
+#[derive(Debug)]
+pub struct FaucetInfo {
+    pub address: Pubkey,
+    pub faucet: Faucet,
+    pub mint: Mint,
+    pub token_program: TokenProgram,
+    /// Token-2022 extensions enabled on the mint.
+    pub mint_extensions: Vec<ExtensionType>,
+}
+
+/// Result of [`FaucetClient::discover`].
+#[derive(Debug)]
+pub struct Discovered {
+    /// Faucets found, with their registry alias if any.
+    pub faucets: Vec<(FaucetInfo, Option<String>)>,
+    /// Aliases the faucets were imported into the registry under.
+    pub imported: Vec<String>,
+}
+
+/// Decoded on-chain state of a mint.
+#[derive(Debug)]
+pub struct MintInfo {
+    pub address: Pubkey,
+    pub mint: Mint,
+    pub token_program: TokenProgram,
+    /// Token-2022 extensions enabled on the mint.
+    pub extensions: Vec<ExtensionType>,
+}
+
+/// Result of [`FaucetClient::inspect_account`].
+#[derive(Debug)]
+pub struct Inspection {
+    pub mint: MintInfo,
+    /// Faucets handing out the mint, keyed by address.
+    pub faucets: Vec<(Pubkey, Faucet)>,
+    /// Faucet PDA, which must be the mint authority for airdrops to succeed.
+    pub pda: Pubkey,
+}
+
+/// Outcome of a transaction simulated instead of sent, see [`TxConfig::dry_run`].
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+    /// Lamports the transaction would deposit into new accounts.
+    pub rent: u64,
+    pub fee: u64,
+}
+
+/// Settings applied to every transaction sent by a [`FaucetClient`].
+#[derive(Debug, Default, Clone)]
+pub struct TxConfig {
+    /// Send transactions even when connected to mainnet-beta.
+    pub allow_mainnet: bool,
+    /// Simulate transactions instead of sending them.
+    pub dry_run: bool,
+    /// Compute unit limit requested by every transaction, `Auto` simulating it first.
+    pub compute_unit_limit: Option<BudgetSetting<u32>>,
+    /// Priority fee in micro-lamports per compute unit, `Auto` using recent fees.
+    pub priority_fee: Option<BudgetSetting<u64>>,
+    /// Durable nonce account whose stored nonce replaces the recent blockhash of every
+    /// transaction, keeping it valid until the nonce is advanced.
+    pub nonce: Option<Pubkey>,
+    /// Sign transactions without sending them, returning [`Sent::SignedOnly`] instead.
+    pub sign_only: bool,
+    /// Blockhash, or durable nonce value, of every transaction instead of the cluster's.
+    pub blockhash: Option<Hash>,
+}
+
+impl TxConfig {
+    /// Whether transactions are built without RPC, from [`Self::blockhash`], and only signed.
+    pub fn is_offline(&self) -> bool {
+        self.sign_only && self.blockhash.is_some()
+    }
+}
+
+/// Client of a faucet program deployment, signing its transactions with a wallet.
+pub struct FaucetClient {
+    /// Connection to the cluster the faucet program is deployed on.
+    pub rpc: RpcClient,
+    /// Faucet admin and mint and metadata authority, and by default the fee payer and nonce
+    /// authority.
+    pub wallet: Box<dyn Signer>,
+    /// Address of the faucet program.
+    pub program_id: Pubkey,
+    /// Settings of every transaction the client sends.
+    pub tx_config: TxConfig,
+    /// Fee payer of every transaction, the wallet if not set.
+    pub fee_payer: Option<Box<dyn Signer>>,
+    /// Funder of the rent of new accounts, the fee payer if not set.
+    pub rent_funder: Option<Box<dyn Signer>>,
+    /// Authority of [`TxConfig::nonce`], the wallet if not set.
+    pub nonce_authority: Option<Box<dyn Signer>>,
+    /// Registry recording the created faucets and resolving their aliases, none if not set.
+    pub registry_path: Option<PathBuf>,
+    /// Cluster of `rpc`, detected once.
+    cluster: OnceCell<Cluster>,
+}
+
+impl FaucetClient {
+    /// Creates a client for the faucet program at [`FAUCET_PROGRAM_ID`].
+    pub fn new(rpc: RpcClient, wallet: Box<dyn Signer>) -> Self {
+        Self::new_with_program_id(rpc, wallet, FAUCET_PROGRAM_ID)
+    }
+
+    /// Creates a client for a faucet program deployed at `program_id`.
+    pub fn new_with_program_id(
+        rpc: RpcClient,
+        wallet: Box<dyn Signer>,
+        program_id: Pubkey,
+    ) -> Self {
+        Self {
+            rpc,
+            wallet,
+            program_id,
+            tx_config: TxConfig::default(),
+            fee_payer: None,
+            rent_funder: None,
+            nonce_authority: None,
+            registry_path: None,
+            cluster: OnceCell::new(),
+        }
+    }
+
+    /// Cluster `rpc` is connected to, detected on the first call.
+    pub async fn cluster(&self) -> Result<Cluster> {
+        self.cluster
+            .get_or_try_init(|| Cluster::detect(&self.rpc))
+            .await
+            .cloned()
+    }
+
+    /// Creates a new mint owned by the faucet PDA and a faucet handing out at most
+    /// `options.max_amount` per airdrop, and records it in the registry.
+    ///
+    /// Fails before sending anything if the alias is already registered on the cluster, unless
+    /// the registered faucet no longer exists, e.g. after a cluster reset.
+    pub async fn create(&self, options: CreateOptions) -> Result<CreatedFaucet> {
+        let CreateOptions {
+            decimals,
+            max_amount,
+            admin,
+            token_program,
+            mut extensions,
+            metadata,
+            mint_keypair,
+            faucet_keypair,
+            out_dir,
+            alias,
+        } = options;
+
+        if !extensions.is_empty() && token_program != TokenProgram::SplToken2022 {
+            bail!("mint extensions are only supported by the spl-token-2022 token program");
+        }
+
+        let amount = max_amount.to_base_units(decimals)?;
+        let mint_authority = get_faucet_pda(&self.program_id).0;
+
+        let alias = alias.or_else(|| metadata.as_ref().map(|metadata| metadata.symbol.clone()));
+        let registry = self.registry().await?;
+        if let (Some(alias), Some((cluster, registry))) = (&alias, &registry) {
+            if let Some(entry) = registry.get(cluster, alias) {
+                let faucet = Pubkey::from_str(&entry.faucet)
+                    .with_context(|| format!("invalid faucet {} in the registry", entry.faucet))?;
+                let exists = self
+                    .rpc
+                    .get_account_with_commitment(&faucet, self.rpc.commitment())
+                    .await?
+                    .value
+                    .is_some();
+                if exists {
+                    bail!(
+                        "a faucet named {} is already registered on {}, pick another alias",
+                        alias,
+                        cluster
+                    );
+                }
+            }
+        }
+
+        // Every signer of an offline transaction must build it with the same addresses.
+        if (self.tx_config.sign_only || self.tx_config.blockhash.is_some())
+            && (mint_keypair.is_none() || faucet_keypair.is_none())
+        {
+            bail!("signing offline needs the mint and faucet keypairs rather than generated ones");
+        }
+
+        let mut keypair_files = vec![];
+        let mint_keypair = match mint_keypair {
+            Some(mint_keypair) => mint_keypair,
+            None => Box::new(generate_keypair(
+                out_dir.as_deref(),
+                "mint",
+                &mut keypair_files,
+            )?),
+        };
+        let faucet_keypair = match faucet_keypair {
+            Some(faucet_keypair) => faucet_keypair,
+            None => Box::new(generate_keypair(
+                out_dir.as_deref(),
+                "faucet",
+                &mut keypair_files,
+            )?),
+        };
+        let mint = mint_keypair.pubkey();
+
+        // Token-2022 mints hold their own metadata, spl-token mints get a Metaplex account.
+        extensions.metadata_pointer =
+            metadata.is_some() && token_program == TokenProgram::SplToken2022;
+        let mint_len = token_program.mint_len(&extensions.extension_types())?;
+        let (mint_rent, metadata_rent, metadata_account) = match (&metadata, token_program) {
+            (Some(metadata), TokenProgram::SplToken2022) => {
+                // The metadata is reallocated into the mint, which must already hold its rent.
+                let mint_rent = self.rent_exemption(mint_len + metadata.tlv_len()?).await?;
+                (mint_rent, 0, Some(mint))
+            }
+            (Some(metadata), TokenProgram::SplToken) => {
+                metadata.check_metaplex_limits()?;
+                let mint_rent = self.rent_exemption(mint_len).await?;
+                let metadata_rent = self.rent_exemption(METAPLEX_METADATA_LEN).await?;
+                (
+                    mint_rent,
+                    metadata_rent,
+                    Some(get_metaplex_metadata_address(&mint)),
+                )
+            }
+            (None, _) => {
+                let mint_rent = self.rent_exemption(mint_len).await?;
+                (mint_rent, 0, None)
+            }
+        };
+        let faucet_rent = self.rent_exemption(Faucet::LEN).await?;
+
+        // Extension and metadata authorities default to the wallet on faucets without an admin.
+        let update_authority = admin.unwrap_or_else(|| self.wallet.pubkey());
+
+        let mut ixs = vec![create_account(
+            &self.rent_funder().pubkey(),
+            &mint,
+            mint_rent,
+            mint_len as u64,
+            &token_program.id(),
+        )];
+        ixs.extend(extensions.initialize_ixs(&token_program.id(), &mint, &update_authority)?);
+
+        match &metadata {
+            // Writing the metadata needs the mint authority to sign, so the wallet holds it until
+            // the metadata exists and only then hands it to the faucet PDA.
+            Some(metadata) => {
+                ixs.push(spl_token_2022::instruction::initialize_mint2(
+                    &token_program.id(),
+                    &mint,
+                    &self.wallet.pubkey(),
+                    None,
+                    decimals,
+                )?);
+                ixs.push(match token_program {
+                    TokenProgram::SplToken => create_metaplex_metadata_ix(
+                        mint,
+                        self.wallet.pubkey(),
+                        self.rent_funder().pubkey(),
+                        update_authority,
+                        metadata,
+                    ),
+                    TokenProgram::SplToken2022 => {
+                        spl_token_metadata_interface::instruction::initialize(
+                            &token_program.id(),
+                            &mint,
+                            &update_authority,
+                            &mint,
+                            &self.wallet.pubkey(),
+                            metadata.name.clone(),
+                            metadata.symbol.clone(),
+                            metadata.uri.clone(),
+                        )
+                    }
+                });
+                ixs.push(spl_token_2022::instruction::set_authority(
+                    &token_program.id(),
+                    &mint,
+                    Some(&mint_authority),
+                    AuthorityType::MintTokens,
+                    &self.wallet.pubkey(),
+                    &[],
+                )?);
+            }
+            None => ixs.push(spl_token_2022::instruction::initialize_mint2(
+                &token_program.id(),
+                &mint,
+                &mint_authority,
+                None,
+                decimals,
+            )?),
+        }
+
+        ixs.extend([
+            create_account(
+                &self.rent_funder().pubkey(),
+                &faucet_keypair.pubkey(),
+                faucet_rent,
+                Faucet::LEN as u64,
+                &self.program_id,
+            ),
+            create_init_faucet_ix(
+                &self.program_id,
+                mint,
+                faucet_keypair.pubkey(),
+                admin,
+                amount,
+            ),
+        ]);
+
+        let sent = match self
+            .send(
+                &ixs,
+                &[mint_keypair.as_ref(), faucet_keypair.as_ref()],
+                mint_rent + metadata_rent + faucet_rent,
+            )
+            .await
+        {
+            Err(err)
+                if token_program == TokenProgram::SplToken2022
+                    && is_token_2022_unsupported(&err, &self.program_id) =>
+            {
+                return Err(err.context(format!(
+                    "the faucet program at {} cannot create faucets of Token-2022 mints, \
+                     it only supports spl-token mints",
+                    self.program_id
+                )));
+            }
+            result => result?,
+        };
+
+        let mut created = CreatedFaucet {
+            mint,
+            faucet: faucet_keypair.pubkey(),
+            pda: mint_authority,
+            admin,
+            token_program,
+            extensions,
+            metadata,
+            metadata_account,
+            decimals,
+            max_amount: amount,
+            keypair_files,
+            alias: None,
+            sent,
+        };
+
+        if let (Sent::Signature(_), Some((cluster, mut registry))) = (&created.sent, registry) {
+            let alias = alias.unwrap_or_else(|| created.faucet.to_string());
+            registry.insert(&cluster, alias.clone(), RegistryEntry::new(&created));
+            self.save_registry(&registry)?;
+            created.alias = Some(alias);
+        }
+
+        Ok(created)
+    }
+
+    /// Creates the faucets of the manifest at `manifest_path` missing from the resolved file
+    /// at `resolved_path`, or whose recorded faucet account no longer exists, e.g. after a
+    /// cluster reset. The resolved file defaults to [`Manifest::resolved_path`].
+    ///
+    /// A token missing from the resolved file whose symbol is registered on the cluster, e.g.
+    /// one made with `create`, is left alone if its faucet exists and recorded in the resolved
+    /// file.
+    ///
+    /// The resolved file is rewritten after each created faucet, so a failed run can be
+    /// resumed. It is left untouched on a dry run. Created faucets are registered under their
+    /// symbol.
+    pub async fn apply(
+        &self,
+        manifest_path: &Path,
+        resolved_path: Option<PathBuf>,
+    ) -> Result<Applied> {
+        if self.tx_config.sign_only {
+            bail!("apply sends a transaction per token and cannot be signed offline");
+        }
+
+        let manifest = Manifest::load(manifest_path)?;
+        let resolved_path = resolved_path.unwrap_or_else(|| Manifest::resolved_path(manifest_path));
+        let mut resolved = Resolved::load(&resolved_path)?;
+        let registry = self.registry().await?;
+        let mut applied = vec![];
+
+        for (symbol, token) in &manifest.tokens {
+            let mut existing = None;
+            if let Some(token) = resolved.tokens.get(symbol) {
+                if self.faucet_exists(&token.faucet).await? {
+                    existing = Some(token.clone());
+                }
+            }
+            let registered = registry
+                .as_ref()
+                .and_then(|(cluster, registry)| registry.get(cluster, symbol));
+            if let (None, Some(entry)) = (&existing, registered) {
+                if self.faucet_exists(&entry.faucet).await? {
+                    let mint = Pubkey::from_str(&entry.mint)
+                        .with_context(|| format!("invalid mint {} in the registry", entry.mint))?;
+                    let token_program =
+                        TokenProgram::from_owner(&self.get_account(&mint).await?.owner)?;
+                    let token = ResolvedToken::registered(entry, token_program);
+
+                    resolved.tokens.insert(symbol.clone(), token.clone());
+                    if !self.tx_config.dry_run {
+                        resolved.save(&resolved_path)?;
+                    }
+                    existing = Some(token);
+                }
+            }
+            if let Some(existing) = existing {
+                applied.push(AppliedToken::Existing {
+                    symbol: symbol.clone(),
+                    token: existing,
+                });
+                continue;
+            }
+
+            let created = self
+                .create(token.create_options(symbol, &self.wallet.pubkey())?)
+                .await
+                .with_context(|| format!("failed to create the {} faucet", symbol))?;
+
+            if let Sent::Signature(signature) = created.sent {
+                resolved.tokens.insert(
+                    symbol.clone(),
+                    ResolvedToken {
+                        mint: created.mint.to_string(),
+                        faucet: created.faucet.to_string(),
+                        decimals: created.decimals,
+                        max_amount: created.max_amount,
+                        token_program: created.token_program.to_string(),
+                        signature: Some(signature.to_string()),
+                    },
+                );
+                resolved.save(&resolved_path)?;
+            }
+
+            applied.push(AppliedToken::Created {
+                symbol: symbol.clone(),
+                created: Box::new(created),
+            });
+        }
+
+        Ok(Applied {
+            tokens: applied,
+            resolved_path,
+        })
+    }
+
+    /// Mints `amount` from `faucet` to the associated token account of `recipient`,
+    /// creating it if needed.
+    ///
+    /// Amounts above the faucet limit are only allowed when the wallet is the faucet admin.
+    pub async fn airdrop(
+        &self,
+        faucet_address: Pubkey,
+        amount: &Amount,
+        recipient: Pubkey,
+    ) -> Result<Airdrop> {
+        let FaucetInfo {
+            faucet,
+            mint,
+            token_program,
+            mint_extensions,
+            ..
+        } = self.inspect(faucet_address).await?;
+
+        let amount = amount.to_base_units(mint.decimals)?;
+
+        let admin = if amount > faucet.amount {
+            match faucet.admin {
+                COption::Some(admin) if admin == self.wallet.pubkey() => Some(admin),
+                _ => bail!(
+                    "amount {} exceeds the faucet limit of {} and needs the admin",
+                    amount,
+                    faucet.amount
+                ),
+            }
+        } else {
+            None
+        };
+
+        let token_account = get_associated_token_address_with_program_id(
+            &recipient,
+            &faucet.mint,
+            &token_program.id(),
+        );
+
+        let mut ixs = vec![];
+        let mut rent = 0;
+        if self
+            .rpc
+            .get_account_with_commitment(&token_account, self.rpc.commitment())
+            .await?
+            .value
+            .is_none()
+        {
+            rent = self
+                .rent_exemption(token_program.associated_token_account_len(&mint_extensions)?)
+                .await?;
+            ixs.push(create_associated_token_account(
+                &self.rent_funder().pubkey(),
+                &recipient,
+                &faucet.mint,
+                &token_program.id(),
+            ));
+        }
+        ixs.push(create_mint_tokens_ix(
+            &self.program_id,
+            &token_program.id(),
+            faucet.mint,
+            token_account,
+            faucet_address,
+            admin,
+            amount,
+        ));
+
+        let sent = match self.send(&ixs, &[], rent).await {
+            Err(err)
+                if token_program == TokenProgram::SplToken2022
+                    && is_token_2022_unsupported(&err, &self.program_id) =>
+            {
+                return Err(err.context(format!(
+                    "the faucet program at {} cannot mint Token-2022 tokens, \
+                     it only supports spl-token mints",
+                    self.program_id
+                )));
+            }
+            result => result?,
+        };
+
+        let balance = match sent {
+            Sent::Signature(_) => Some(
+                self.rpc
+                    .get_token_account_balance_with_commitment(
+                        &token_account,
+                        self.rpc.commitment(),
+                    )
+                    .await?
+                    .value
+                    .ui_amount_string,
+            ),
+            Sent::Simulated(..) | Sent::SignedOnly(_) => None,
+        };
+
+        Ok(Airdrop {
+            token_account,
+            balance,
+            sent,
+        })
+    }
+
+    /// Airdrops the amount of every row of the recipients file at `csv_path` from
+    /// `faucet_address` to the associated token account of its wallet, creating it if needed.
+    ///
+    /// Results are written to `results_path`, [`batch::results_path`] by default, whenever a
+    /// transaction settles, except on a dry run. Rows that succeeded in an earlier run, as
+    /// recorded there, are skipped. Amounts are in UI units unless `raw` is set.
+    pub async fn airdrop_batch(
+        &self,
+        faucet_address: Pubkey,
+        csv_path: &Path,
+        results_path: Option<PathBuf>,
+        raw: bool,
+        max_in_flight: usize,
+    ) -> Result<BatchAirdrop> {
+        if self.tx_config.sign_only {
+            bail!("airdrop-batch sends many transactions and cannot be signed offline");
+        }
+
+        let results_path = results_path.unwrap_or_else(|| batch::results_path(csv_path));
+        let previous = batch::read_results(&results_path)?;
+        let (mut rows, mut skipped) =
+            batch::pending_rows(batch::read_recipients(csv_path)?, &previous);
+
+        // Airdrops that failed to confirm may have landed after all.
+        let landed = self
+            .landed(
+                rows.iter()
+                    .filter_map(|row| batch::failed_signature(row, &previous)),
+            )
+            .await?;
+        rows.retain(|row| match batch::failed_signature(row, &previous) {
+            Some(signature) if landed.contains(&signature) => {
+                skipped.push(BatchResult {
+                    signature: Some(signature.to_string()),
+                    ..BatchResult::new(row, BatchStatus::Skipped)
+                });
+                false
+            }
+            _ => true,
+        });
+
+        let save = |results: &[BatchResult]| {
+            if self.tx_config.dry_run {
+                return Ok(());
+            }
+            let mut all = skipped.clone();
+            all.extend_from_slice(results);
+            all.sort_by_key(|result| result.line);
+            batch::write_results(&results_path, &all)
+        };
+        let mut results = self
+            .send_batch(faucet_address, &rows, raw, max_in_flight, save)
+            .await?;
+        results.extend(skipped);
+        results.sort_by_key(|result| result.line);
+
+        Ok(BatchAirdrop {
+            results,
+            results_path,
+        })
+    }
+
+    /// Those of `signatures` whose transaction landed without error.
+    async fn landed(
+        &self,
+        signatures: impl Iterator<Item = Signature>,
+    ) -> Result<HashSet<Signature>> {
+        let mut signatures: Vec<Signature> = signatures.collect();
+        signatures.sort();
+        signatures.dedup();
+
+        let mut landed = HashSet::new();
+        for signatures in signatures.chunks(MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS) {
+            let statuses = self
+                .rpc
+                .get_signature_statuses_with_history(signatures)
+                .await?
+                .value;
+            landed.extend(
+                signatures
+                    .iter()
+                    .zip(statuses)
+                    .filter(|(_, status)| {
+                        status.as_ref().is_some_and(|status| status.err.is_none())
+                    })
+                    .map(|(signature, _)| *signature),
+            );
+        }
+        Ok(landed)
+    }
+
+    /// Airdrops every row of `rows`, packed into as few transactions as the packet size and
+    /// compute limits allow.
+    ///
+    /// At most `max_in_flight` transactions await confirmation at once, or one at a time with
+    /// a durable nonce since every transaction advances it. A row with an invalid amount or a
+    /// failed transaction fails on its own, and `on_result` is called with the results so far
+    /// whenever a transaction settles.
+    async fn send_batch(
+        &self,
+        faucet_address: Pubkey,
+        rows: &[BatchRow],
+        raw: bool,
+        max_in_flight: usize,
+        mut on_result: impl FnMut(&[BatchResult]) -> Result<()>,
+    ) -> Result<Vec<BatchResult>> {
+        let FaucetInfo {
+            faucet,
+            mint,
+            token_program,
+            mint_extensions,
+            ..
+        } = self.inspect(faucet_address).await?;
+        let is_admin = faucet.admin == COption::Some(self.wallet.pubkey());
+
+        let mut results = vec![];
+        let mut airdrops = vec![];
+        for row in rows {
+            let amount = Amount::parse(&row.amount, raw)
+                .and_then(|amount| amount.to_base_units(mint.decimals));
+            let error = match amount {
+                Ok(amount) if amount <= faucet.amount || is_admin => {
+                    airdrops.push((row, amount));
+                    continue;
+                }
+                Ok(amount) => format!(
+                    "amount {} exceeds the faucet limit of {} and needs the admin",
+                    amount, faucet.amount
+                ),
+                Err(err) => err.to_string(),
+            };
+            results.push(BatchResult {
+                error: Some(error),
+                ..BatchResult::new(row, BatchStatus::Failed)
+            });
+        }
+
+        let token_account = |wallet: &Pubkey| {
+            get_associated_token_address_with_program_id(wallet, &faucet.mint, &token_program.id())
+        };
+
+        let mut wallets: Vec<Pubkey> = airdrops.iter().map(|(row, _)| row.wallet).collect();
+        wallets.sort();
+        wallets.dedup();
+        let mut missing_accounts = HashSet::new();
+        for wallets in wallets.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let token_accounts: Vec<Pubkey> = wallets.iter().map(token_account).collect();
+            let accounts = self
+                .rpc
+                .get_multiple_accounts_with_commitment(&token_accounts, self.rpc.commitment())
+                .await?
+                .value;
+            missing_accounts.extend(
+                wallets
+                    .iter()
+                    .zip(accounts)
+                    .filter(|(_, account)| account.is_none())
+                    .map(|(wallet, _)| *wallet),
+            );
+        }
+
+        let mut budget_ixs: Vec<Instruction> = self
+            .tx_config
+            .nonce
+            .map(|nonce| advance_nonce_account(&nonce, &self.nonce_authority().pubkey()))
+            .into_iter()
+            .collect();
+        budget_ixs.extend(compute_budget_ixs(
+            self.tx_config
+                .compute_unit_limit
+                .map(|_| MAX_COMPUTE_UNIT_LIMIT),
+            self.tx_config.priority_fee.map(|_| u64::MAX),
+        ));
+        let compute_unit_limit = match self.tx_config.compute_unit_limit {
+            Some(BudgetSetting::Fixed(compute_unit_limit)) => compute_unit_limit,
+            _ => MAX_COMPUTE_UNIT_LIMIT,
+        };
+
+        let mut txs = vec![];
+        let mut tx = PackedTx::default();
+        for (row, amount) in airdrops {
+            let admin = (amount > faucet.amount).then(|| self.wallet.pubkey());
+            let create_account_ix = missing_accounts.contains(&row.wallet).then(|| {
+                create_associated_token_account_idempotent(
+                    &self.rent_funder().pubkey(),
+                    &row.wallet,
+                    &faucet.mint,
+                    &token_program.id(),
+                )
+            });
+            let mint_tokens_ix = create_mint_tokens_ix(
+                &self.program_id,
+                &token_program.id(),
+                faucet.mint,
+                token_account(&row.wallet),
+                faucet_address,
+                admin,
+                amount,
+            );
+
+            let pushed = tx.try_push(
+                row,
+                create_account_ix.clone(),
+                mint_tokens_ix.clone(),
+                &self.fee_payer().pubkey(),
+                &budget_ixs,
+                compute_unit_limit,
+            );
+            if !pushed {
+                txs.push(std::mem::take(&mut tx));
+                tx.try_push(
+                    row,
+                    create_account_ix,
+                    mint_tokens_ix,
+                    &self.fee_payer().pubkey(),
+                    &budget_ixs,
+                    compute_unit_limit,
+                );
+            }
+        }
+        if !tx.rows.is_empty() {
+            txs.push(tx);
+        }
+
+        let account_rent = self
+            .rent_exemption(token_program.associated_token_account_len(&mint_extensions)?)
+            .await?;
+
+        on_result(&results)?;
+        let mut sent = stream::iter(txs)
+            .map(|tx| async move {
+                let rent = account_rent * tx.created_accounts.len() as u64;
+                let result = self.send_with(&tx.ixs, &[], rent, false).await;
+                (tx.rows, result)
+            })
+            .buffer_unordered(match self.tx_config.nonce {
+                Some(_) => 1,
+                None => max_in_flight.max(1),
+            });
+
+        while let Some((rows, result)) = sent.next().await {
+            for row in rows {
+                results.push(match &result {
+                    Ok(Sent::Signature(signature)) => BatchResult {
+                        signature: Some(signature.to_string()),
+                        ..BatchResult::new(row, BatchStatus::Ok)
+                    },
+                    // Batches are never signed only.
+                    Ok(Sent::Simulated(..) | Sent::SignedOnly(_)) => {
+                        BatchResult::new(row, BatchStatus::Simulated)
+                    }
+                    // Recorded so that the next run finds out whether the transaction landed.
+                    Err(err) => BatchResult {
+                        signature: err
+                            .downcast_ref::<SentTransaction>()
+                            .map(|sent| sent.signature.to_string()),
+                        error: Some(self.batch_error(err, token_program)),
+                        ..BatchResult::new(row, BatchStatus::Failed)
+                    },
+                });
+            }
+            on_result(&results)?;
+        }
+
+        results.sort_by_key(|result| result.line);
+        Ok(results)
+    }
+
+    /// Creates a durable nonce account owned by `authority`, the wallet if not set, at
+    /// `nonce_keypair` or a generated address.
+    pub async fn create_nonce(
+        &self,
+        nonce_keypair: Option<Box<dyn Signer>>,
+        authority: Option<Pubkey>,
+    ) -> Result<CreatedNonce> {
+        let nonce_keypair = nonce_keypair.unwrap_or_else(|| Box::new(Keypair::new()));
+        let authority = authority.unwrap_or_else(|| self.wallet.pubkey());
+
+        let lamports = self.rent_exemption(nonce::State::size()).await?;
+        let ixs = create_nonce_account(
+            &self.rent_funder().pubkey(),
+            &nonce_keypair.pubkey(),
+            &authority,
+            lamports,
+        );
+
+        let sent = self.send(&ixs, &[nonce_keypair.as_ref()], lamports).await?;
+
+        Ok(CreatedNonce {
+            nonce: nonce_keypair.pubkey(),
+            authority,
+            lamports,
+            sent,
+        })
+    }
+
+    /// Closes `faucet_address` and sends its rent to `destination`.
+    ///
+    /// The wallet must be the faucet admin; faucets created without an admin cannot be closed.
+    /// Offline, the faucet is closed without checking its admin and its rent is unknown.
+    pub async fn close(&self, faucet_address: Pubkey, destination: Pubkey) -> Result<ClosedFaucet> {
+        let lamports = if self.tx_config.is_offline() {
+            0
+        } else {
+            let faucet_account = self.get_account(&faucet_address).await?;
+            let faucet = Faucet::unpack(&faucet_account.data)?;
+
+            match faucet.admin {
+                COption::Some(admin) if admin == self.wallet.pubkey() => {}
+                COption::Some(admin) => bail!(
+                    "faucet {} can only be closed by its admin {}, not {}",
+                    faucet_address,
+                    admin,
+                    self.wallet.pubkey()
+                ),
+                COption::None => bail!(
+                    "faucet {} has no admin and can never be closed",
+                    faucet_address
+                ),
+            }
+            faucet_account.lamports
+        };
+
+        let sent = self
+            .send(
+                &[create_close_faucet_ix(
+                    &self.program_id,
+                    self.wallet.pubkey(),
+                    destination,
+                    faucet_address,
+                )],
+                &[],
+                0,
+            )
+            .await?;
+
+        Ok(ClosedFaucet {
+            faucet: faucet_address,
+            destination,
+            lamports,
+            sent,
+        })
+    }
+
+    /// Updates the metadata of the mint of `faucet_address`.
+    ///
+    /// The wallet must be the metadata update authority, which defaults to the faucet admin.
+    /// The current metadata is read over RPC, so the update cannot be signed offline.
+    pub async fn update_metadata(
+        &self,
+        faucet_address: Pubkey,
+        update: MetadataUpdate,
+    ) -> Result<UpdatedMetadata> {
+        if self.tx_config.is_offline() {
+            bail!(
+                "update-metadata reads the current metadata over RPC and cannot be signed \
+                 offline, pass --sign-only without --blockhash"
+            );
+        }
+
+        let faucet = Faucet::unpack(&self.get_account(&faucet_address).await?.data)?;
+        let mint_account = self.get_account(&faucet.mint).await?;
+        let wallet = self.wallet.pubkey();
+
+        let mut ixs = vec![];
+        let mut rent = 0;
+        let (metadata_account, metadata) = match TokenProgram::from_owner(&mint_account.owner)? {
+            TokenProgram::SplToken => {
+                let metadata_account = get_metaplex_metadata_address(&faucet.mint);
+                let existing = match self
+                    .rpc
+                    .get_account_with_commitment(&metadata_account, self.rpc.commitment())
+                    .await?
+                    .value
+                {
+                    Some(account) => Metadata::from_bytes(&account.data)?,
+                    None => bail!("mint {} has no Metaplex metadata", faucet.mint),
+                };
+                let update_authority =
+                    Some(existing.update_authority).filter(|_| existing.is_mutable);
+                check_update_authority(&faucet.mint, update_authority, &wallet)?;
+
+                let mut metadata = TokenMetadata::from_metaplex(&existing);
+                if metadata.apply(update) {
+                    metadata.check_metaplex_limits()?;
+                    ixs.push(update_metaplex_metadata_ix(&existing, wallet, &metadata));
+                }
+                (metadata_account, metadata)
+            }
+            TokenProgram::SplToken2022 => {
+                let existing = StateWithExtensions::<Mint>::unpack(&mint_account.data)?
+                    .get_variable_len_extension::<Token2022Metadata>()
+                    .map_err(|_| anyhow!("mint {} has no Token-2022 metadata", faucet.mint))?;
+                check_update_authority(&faucet.mint, existing.update_authority.into(), &wallet)?;
+
+                let before = TokenMetadata::from_token_2022(&existing);
+                let mut metadata = before.clone();
+                metadata.apply(update);
+                for (field, old, new) in [
+                    (Field::Name, &before.name, &metadata.name),
+                    (Field::Symbol, &before.symbol, &metadata.symbol),
+                    (Field::Uri, &before.uri, &metadata.uri),
+                ] {
+                    if old != new {
+                        ixs.push(spl_token_metadata_interface::instruction::update_field(
+                            &spl_token_2022::ID,
+                            &faucet.mint,
+                            &wallet,
+                            field,
+                            new.clone(),
+                        ));
+                    }
+                }
+
+                // Longer fields are reallocated into the mint, which must stay rent exempt.
+                if !ixs.is_empty() {
+                    let mint_len =
+                        mint_account.data.len() + metadata.tlv_len()? - before.tlv_len()?;
+                    rent = self
+                        .rent_exemption(mint_len)
+                        .await?
+                        .saturating_sub(mint_account.lamports);
+                    if rent > 0 {
+                        ixs.insert(
+                            0,
+                            transfer(&self.rent_funder().pubkey(), &faucet.mint, rent),
+                        );
+                    }
+                }
+                (faucet.mint, metadata)
+            }
+        };
+
+        if ixs.is_empty() {
+            bail!("metadata of mint {} is already up to date", faucet.mint);
+        }
+
+        let sent = self.send(&ixs, &[], rent).await?;
+
+        Ok(UpdatedMetadata {
+            mint: faucet.mint,
+            metadata_account,
+            metadata,
+            sent,
+        })
+    }
+
+    /// Fetches and decodes `faucet_address` and its mint.
+    pub async fn inspect(&self, faucet_address: Pubkey) -> Result<FaucetInfo> {
+        let faucet = Faucet::unpack(&self.get_account(&faucet_address).await?.data)?;
+        let mint_account = self.get_account(&faucet.mint).await?;
+        let mint = decode_mint(faucet.mint, &mint_account)?;
+
+        Ok(FaucetInfo {
+            address: faucet_address,
+            token_program: mint.token_program,
+            mint_extensions: mint.extensions,
+            mint: mint.mint,
+            faucet,
+        })
+    }
+
+    /// Fetches and decodes `address`, either a faucet or a mint, along with the mint and its
+    /// faucets.
+    pub async fn inspect_account(&self, address: Pubkey) -> Result<Inspection> {
+        let account = self.get_account(&address).await?;
+
+        let (mint, faucets) = if account.owner == self.program_id {
+            let faucet = Faucet::unpack(&account.data)?;
+            let mint_account = self.get_account(&faucet.mint).await?;
+            (
+                decode_mint(faucet.mint, &mint_account)?,
+                vec![(address, faucet)],
+            )
+        } else if TokenProgram::from_owner(&account.owner).is_ok() {
+            (
+                decode_mint(address, &account)?,
+                self.find_faucets(Some(address), None).await?,
+            )
+        } else {
+            bail!(
+                "{} is neither a faucet of program {} nor a mint, its owner is {}",
+                address,
+                self.program_id,
+                account.owner
+            );
+        };
+
+        Ok(Inspection {
+            mint,
+            faucets,
+            pda: get_faucet_pda(&self.program_id).0,
+        })
+    }
+
+    /// Faucets of the program, only those of `mint` and administered by `admin` if set, found
+    /// with `getProgramAccounts`.
+    pub async fn find_faucets(
+        &self,
+        mint: Option<Pubkey>,
+        admin: Option<Pubkey>,
+    ) -> Result<Vec<(Pubkey, Faucet)>> {
+        let mut filters = vec![RpcFilterType::DataSize(Faucet::LEN as u64)];
+        if let Some(mint) = mint {
+            filters.push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                FAUCET_MINT_OFFSET,
+                mint.as_ref(),
+            )));
+        }
+        if let Some(admin) = admin {
+            // The `COption::Some` tag followed by the admin.
+            let mut bytes = vec![1, 0, 0, 0];
+            bytes.extend_from_slice(admin.as_ref());
+            filters.push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                FAUCET_ADMIN_OFFSET,
+                &bytes,
+            )));
+        }
+
+        let accounts = self
+            .rpc
+            .get_program_accounts_with_config(
+                &self.program_id,
+                RpcProgramAccountsConfig {
+                    filters: Some(filters),
+                    account_config: RpcAccountInfoConfig {
+                        encoding: Some(UiAccountEncoding::Base64),
+                        ..RpcAccountInfoConfig::default()
+                    },
+                    ..RpcProgramAccountsConfig::default()
+                },
+            )
+            .await?;
+
+        accounts
+            .into_iter()
+            .map(|(address, account)| Ok((address, Faucet::unpack(&account.data)?)))
+            .collect()
+    }
+
+    /// Decodes the faucets found by [`Self::find_faucets`] along with their mints, importing
+    /// those missing from the registry under their address if `import` is set.
+    pub async fn discover(
+        &self,
+        mint: Option<Pubkey>,
+        admin: Option<Pubkey>,
+        import: bool,
+    ) -> Result<Discovered> {
+        let found = self
+            .decode_faucets(self.find_faucets(mint, admin).await?)
+            .await?;
+
+        let mut imported = vec![];
+        let registry = match self.registry().await? {
+            Some((cluster, mut registry)) => {
+                if import {
+                    for info in &found {
+                        if registry.alias_of(&cluster, &info.address).is_none() {
+                            let alias = info.address.to_string();
+                            registry.insert(
+                                &cluster,
+                                alias.clone(),
+                                RegistryEntry::discovered(info),
+                            );
+                            imported.push(alias);
+                        }
+                    }
+                    if !imported.is_empty() {
+                        self.save_registry(&registry)?;
+                    }
+                }
+                Some((cluster, registry))
+            }
+            None => None,
+        };
+
+        let faucets = found
+            .into_iter()
+            .map(|info| {
+                let alias = registry
+                    .as_ref()
+                    .and_then(|(cluster, registry)| registry.alias_of(cluster, &info.address))
+                    .cloned();
+                (info, alias)
+            })
+            .collect();
+
+        Ok(Discovered { faucets, imported })
+    }
+
+    /// Faucet given either as an address or as an alias registered on the cluster.
+    pub async fn resolve_faucet(&self, faucet: &str) -> Result<Pubkey> {
+        if let Ok(faucet) = Pubkey::from_str(faucet) {
+            return Ok(faucet);
+        }
+        match self.registry().await? {
+            Some((cluster, registry)) => registry.resolve_faucet(&cluster, faucet),
+            None => bail!(
+                "{} is not a faucet address, and aliases need the registry and the cluster",
+                faucet
+            ),
+        }
+    }
+
+    /// Faucets registered on the cluster keyed `cluster`, see [`Cluster::key`], or on the
+    /// cluster of `rpc` if not given, or on every cluster if `all` is set.
+    ///
+    /// Only reaches the cluster to detect it, when neither `cluster` nor `all` is given.
+    pub async fn registered(&self, cluster: Option<&str>, all: bool) -> Result<Vec<Registered>> {
+        let registry = self.load_registry()?;
+        if all {
+            return Ok(registry.all().collect());
+        }
+        let cluster = self.cluster_key(cluster).await?;
+        Ok(registry
+            .entries(&cluster)
+            .map(|(alias, entry)| Registered::new(&cluster, alias, entry))
+            .collect())
+    }
+
+    /// Faucet registered as `alias` on the cluster keyed `cluster`, or on the cluster of `rpc`
+    /// if not given.
+    pub async fn registered_faucet(
+        &self,
+        alias: &str,
+        cluster: Option<&str>,
+    ) -> Result<Registered> {
+        let cluster = self.cluster_key(cluster).await?;
+        let registry = self.load_registry()?;
+        let entry = registry
+            .get(&cluster, alias)
+            .ok_or_else(|| anyhow!("no faucet named {} on {}", alias, cluster))?;
+        Ok(Registered::new(&cluster, alias, entry))
+    }
+
+    /// Removes `alias` from the registry of the cluster keyed `cluster`, or of the cluster of
+    /// `rpc` if not given, leaving the faucet on-chain.
+    pub async fn forget(&self, alias: &str, cluster: Option<&str>) -> Result<Registered> {
+        let cluster = self.cluster_key(cluster).await?;
+        let mut registry = self.load_registry()?;
+        let entry = registry.remove(&cluster, alias)?;
+        self.save_registry(&registry)?;
+        Ok(Registered::new(&cluster, alias, &entry))
+    }
+
+    /// Decodes `faucets` along with their mints.
+    async fn decode_faucets(&self, faucets: Vec<(Pubkey, Faucet)>) -> Result<Vec<FaucetInfo>> {
+        let mut mint_addresses: Vec<Pubkey> =
+            faucets.iter().map(|(_, faucet)| faucet.mint).collect();
+        mint_addresses.sort();
+        mint_addresses.dedup();
+
+        let mut mints = HashMap::new();
+        for chunk in mint_addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let accounts = self.rpc.get_multiple_accounts(chunk).await?;
+            for (address, account) in chunk.iter().zip(accounts) {
+                let account = account.ok_or_else(|| anyhow!("mint {} does not exist", address))?;
+                mints.insert(*address, decode_mint(*address, &account)?);
+            }
+        }
+
+        Ok(faucets
+            .into_iter()
+            .map(|(address, faucet)| {
+                let mint = &mints[&faucet.mint];
+                FaucetInfo {
+                    address,
+                    mint: mint.mint,
+                    token_program: mint.token_program,
+                    mint_extensions: mint.extensions.clone(),
+                    faucet,
+                }
+            })
+            .collect())
+    }
+
+    /// Signs `ixs` with `signers` and whichever of the fee payer, wallet and rent funder they
+    /// need, and sends them in a single transaction, or only simulates it on a dry run.
+    ///
+    /// `rent` is the lamports the transaction deposits into new accounts, used to report its
+    /// cost.
+    async fn send(&self, ixs: &[Instruction], signers: &[&dyn Signer], rent: u64) -> Result<Sent> {
+        self.send_with(ixs, signers, rent, true).await
+    }
+
+    /// [`Self::send`], showing a spinner while the transaction confirms only if `spinner` is
+    /// set, which concurrent sends leave unset.
+    async fn send_with(
+        &self,
+        ixs: &[Instruction],
+        signers: &[&dyn Signer],
+        rent: u64,
+        spinner: bool,
+    ) -> Result<Sent> {
+        // Offline signers must all build the same transaction, estimates would differ.
+        if (self.tx_config.sign_only || self.tx_config.blockhash.is_some())
+            && (self.tx_config.compute_unit_limit == Some(BudgetSetting::Auto)
+                || self.tx_config.priority_fee == Some(BudgetSetting::Auto))
+        {
+            bail!("signing offline needs fixed compute unit limits and priority fees, not `auto`");
+        }
+
+        let mut all_signers = vec![self.fee_payer(), self.wallet.as_ref(), self.rent_funder()];
+        all_signers.extend_from_slice(signers);
+
+        let advance_nonce_ix = self.tx_config.nonce.map(|nonce| {
+            all_signers.push(self.nonce_authority());
+            advance_nonce_account(&nonce, &self.nonce_authority().pubkey())
+        });
+        let blockhash = match (self.tx_config.blockhash, self.tx_config.nonce) {
+            (Some(blockhash), _) => blockhash,
+            (None, Some(nonce)) => self.nonce_data(&nonce).await?.blockhash(),
+            (None, None) => self.rpc.get_latest_blockhash().await?,
+        };
+        let sign = |compute_unit_limit, priority_fee| -> Result<Transaction> {
+            // Advancing the nonce must come first.
+            let mut budgeted_ixs: Vec<Instruction> = advance_nonce_ix.iter().cloned().collect();
+            budgeted_ixs.extend(compute_budget_ixs(compute_unit_limit, priority_fee));
+            budgeted_ixs.extend_from_slice(ixs);
+
+            let mut tx =
+                Transaction::new_with_payer(&budgeted_ixs, Some(&self.fee_payer().pubkey()));
+            // Only the signers the instructions need, the wallet or funder may not be.
+            let signer_keys = tx.message.signer_keys();
+            let tx_signers: Vec<&dyn Signer> = all_signers
+                .iter()
+                .copied()
+                .filter(|signer| signer_keys.contains(&&signer.pubkey()))
+                .collect();
+            tx.try_partial_sign(&tx_signers, blockhash)
+                .context("failed to sign the transaction")?;
+            Ok(tx)
+        };
+
+        let priority_fee = match self.tx_config.priority_fee {
+            Some(BudgetSetting::Fixed(priority_fee)) => Some(priority_fee),
+            Some(BudgetSetting::Auto) => {
+                let mut writable_accounts: Vec<Pubkey> = ixs
+                    .iter()
+                    .flat_map(|ix| &ix.accounts)
+                    .filter(|account| account.is_writable)
+                    .map(|account| account.pubkey)
+                    .collect();
+                writable_accounts.sort();
+                writable_accounts.dedup();
+
+                Some(recent_priority_fee(&self.rpc, &writable_accounts).await?)
+            }
+            None => None,
+        };
+
+        let compute_unit_limit = match self.tx_config.compute_unit_limit {
+            Some(BudgetSetting::Fixed(compute_unit_limit)) => Some(compute_unit_limit),
+            Some(BudgetSetting::Auto) => {
+                let tx = sign(Some(MAX_COMPUTE_UNIT_LIMIT), priority_fee)?;
+                let (_, units_consumed) = self.simulate(&tx).await?;
+
+                units_consumed.map(compute_unit_limit_with_margin)
+            }
+            None => None,
+        };
+
+        let tx = sign(compute_unit_limit, priority_fee)?;
+
+        if self.tx_config.sign_only {
+            return Ok(Sent::SignedOnly(tx));
+        }
+
+        if self.tx_config.dry_run {
+            let (logs, units_consumed) = self.simulate(&tx).await?;
+
+            let simulation = Simulation {
+                logs,
+                units_consumed,
+                rent,
+                fee: self.rpc.get_fee_for_message(&tx.message).await?,
+            };
+            return Ok(Sent::Simulated(tx.signatures[0], simulation));
+        }
+
+        if !self.tx_config.allow_mainnet {
+            let cluster = self.cluster().await?;
+            if cluster.is_mainnet() {
+                let fee = self.rpc.get_fee_for_message(&tx.message).await?;
+                bail!(
+                    "refusing to send a transaction costing an estimated {} lamports to {} ({}), \
+                     pass --allow-mainnet to proceed",
+                    fee + rent,
+                    cluster,
+                    self.rpc.url()
+                );
+            }
+        }
+
+        let signature = if spinner {
+            self.rpc
+                .send_and_confirm_transaction_with_spinner_and_commitment(
+                    &tx,
+                    self.rpc.commitment(),
+                )
+                .await
+        } else {
+            self.rpc.send_and_confirm_transaction(&tx).await
+        }
+        .map_err(|err| {
+            anyhow::Error::from(Error::failed_send(&tx, &self.program_id, err)).context(
+                SentTransaction {
+                    signature: tx.signatures[0],
+                },
+            )
+        })?;
+
+        Ok(Sent::Signature(signature))
+    }
+
+    fn fee_payer(&self) -> &dyn Signer {
+        self.fee_payer.as_deref().unwrap_or(self.wallet.as_ref())
+    }
+
+    fn rent_funder(&self) -> &dyn Signer {
+        self.rent_funder.as_deref().unwrap_or(self.fee_payer())
+    }
+
+    fn nonce_authority(&self) -> &dyn Signer {
+        self.nonce_authority
+            .as_deref()
+            .unwrap_or(self.wallet.as_ref())
+    }
+
+    /// Whether `faucet` exists and is owned by the faucet program.
+    async fn faucet_exists(&self, faucet: &str) -> Result<bool> {
+        let faucet =
+            Pubkey::from_str(faucet).with_context(|| format!("invalid faucet {}", faucet))?;
+        let account = self
+            .rpc
+            .get_account_with_commitment(&faucet, self.rpc.commitment())
+            .await?
+            .value;
+        Ok(matches!(account, Some(account) if account.owner == self.program_id))
+    }
+
+    /// Key of the cluster the faucets are recorded under and the registry, none without a
+    /// registry or offline, where the cluster is unknown.
+    async fn registry(&self) -> Result<Option<(String, Registry)>> {
+        if self.registry_path.is_none() || self.tx_config.is_offline() {
+            return Ok(None);
+        }
+        Ok(Some((self.cluster().await?.key(), self.load_registry()?)))
+    }
+
+    /// `cluster`, or the key of the cluster of `rpc` if not given.
+    async fn cluster_key(&self, cluster: Option<&str>) -> Result<String> {
+        match cluster {
+            Some(cluster) => Ok(cluster::key_from_str(cluster)),
+            None => Ok(self.cluster().await?.key()),
+        }
+    }
+
+    /// Registry at [`Self::registry_path`], empty if not set.
+    fn load_registry(&self) -> Result<Registry> {
+        match &self.registry_path {
+            Some(path) => Ok(Registry::load(path).map_err(Error::Config)?),
+            None => Ok(Registry::default()),
+        }
+    }
+
+    fn save_registry(&self, registry: &Registry) -> Result<()> {
+        if let Some(path) = &self.registry_path {
+            registry.save(path).map_err(Error::Config)?;
+        }
+        Ok(())
+    }
+
+    /// Lamports exempting an account of `len` bytes from rent, from the default rent offline.
+    async fn rent_exemption(&self, len: usize) -> Result<u64> {
+        if self.tx_config.is_offline() {
+            return Ok(Rent::default().minimum_balance(len));
+        }
+        Ok(self.rpc.get_minimum_balance_for_rent_exemption(len).await?)
+    }
+
+    /// Fetches `address`, failing with an [`Error::InvalidInput`] if it does not exist.
+    async fn get_account(&self, address: &Pubkey) -> Result<Account> {
+        self.rpc
+            .get_account_with_commitment(address, self.rpc.commitment())
+            .await?
+            .value
+            .ok_or_else(|| {
+                Error::InvalidInput(anyhow!("account {} does not exist", address)).into()
+            })
+    }
+
+    /// Stored state of the durable nonce account `nonce`, failing unless
+    /// [`Self::nonce_authority`] may advance it.
+    async fn nonce_data(&self, nonce: &Pubkey) -> Result<NonceData> {
+        let account = self.get_account(nonce).await?;
+        let data = nonce_utils::data_from_account(&account)
+            .with_context(|| format!("{} is not an initialized nonce account", nonce))?;
+
+        let authority = self.nonce_authority().pubkey();
+        if data.authority != authority {
+            bail!(
+                "nonce account {} is advanced by {}, not {}",
+                nonce,
+                data.authority,
+                authority
+            );
+        }
+        Ok(data)
+    }
+
+    /// One-line error of a failed `airdrop_batch` transaction, for its results file.
+    fn batch_error(&self, err: &anyhow::Error, token_program: TokenProgram) -> String {
+        if token_program == TokenProgram::SplToken2022
+            && is_token_2022_unsupported(err, &self.program_id)
+        {
+            return format!(
+                "the faucet program at {} cannot mint Token-2022 tokens",
+                self.program_id
+            );
+        }
+        format!("{:#}", err)
+    }
+
+    /// Simulates `tx`, returning its logs and consumed compute units, or failing with the logs
+    /// if it errors.
+    async fn simulate(&self, tx: &Transaction) -> Result<(Vec<String>, Option<u64>)> {
+        let result = self.rpc.simulate_transaction(tx).await?.value;
+        let logs = result.logs.unwrap_or_default();
+
+        if let Some(err) = result.err {
+            return Err(Error::failed_transaction(tx, &self.program_id, err, logs).into());
+        }
+
+        Ok((logs, result.units_consumed))
+    }
+}
+
+/// Context of the failure of a transaction that was sent, which may still have landed.
+#[derive(Debug)]
+struct SentTransaction {
+    signature: Signature,
+}
+
+impl fmt::Display for SentTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sent transaction {}", self.signature)
+    }
+}
+
+/// What became of a transaction built by [`FaucetClient`], as [`TxConfig`] decides.
+#[derive(Debug)]
+pub enum Sent {
+    /// Sent and confirmed.
+    Signature(Signature),
+    /// Only simulated on a dry run, with the signature it would have had.
+    Simulated(Signature, Simulation),
+    /// Signed under [`TxConfig::sign_only`] and not sent, missing the offline signatures.
+    SignedOnly(Transaction),
+}
+
+impl Sent {
+    /// Signature of the transaction, whether or not it was sent.
+    pub fn signature(&self) -> Signature {
+        match self {
+            Self::Signature(signature) | Self::Simulated(signature, _) => *signature,
+            Self::SignedOnly(tx) => tx.signatures[0],
+        }
+    }
+
+    pub fn simulation(&self) -> Option<&Simulation> {
+        match self {
+            Self::Simulated(_, simulation) => Some(simulation),
+            Self::Signature(_) | Self::SignedOnly(_) => None,
+        }
+    }
+}
+
+/// Decodes `account` as the spl-token or Token-2022 mint at `address`.
+fn decode_mint(address: Pubkey, account: &Account) -> Result<MintInfo> {
+    let token_program = TokenProgram::from_owner(&account.owner)?;
+    let mint = StateWithExtensions::<Mint>::unpack(&account.data)
+        .with_context(|| format!("{} is not a mint", address))?;
+
+    Ok(MintInfo {
+        address,
+        mint: mint.base,
+        token_program,
+        extensions: mint.get_extension_types()?,
+    })
+}
+
+/// Whether `err` is how a faucet deployment built against spl-token rejects a Token-2022 mint:
+/// `IncorrectProgramId` from any instruction, or `InvalidAccountData` from the faucet program at
+/// `program_id` failing to unpack the mint.
+fn is_token_2022_unsupported(err: &anyhow::Error, program_id: &Pubkey) -> bool {
+    match err.downcast_ref::<Error>() {
+        Some(Error::Transaction {
+            err: TransactionError::InstructionError(_, err),
+            program,
+            ..
+        }) => match err {
+            InstructionError::IncorrectProgramId => true,
+            InstructionError::InvalidAccountData => program.as_ref() == Some(program_id),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Fails unless `wallet` is `update_authority`, `None` meaning the metadata of `mint` is
+/// immutable.
+fn check_update_authority(
+    mint: &Pubkey,
+    update_authority: Option<Pubkey>,
+    wallet: &Pubkey,
+) -> Result<()> {
+    match update_authority {
+        Some(update_authority) if update_authority == *wallet => Ok(()),
+        Some(update_authority) => bail!(
+            "metadata of mint {} can only be updated by {}, not {}",
+            mint,
+            update_authority,
+            wallet
+        ),
+        None => bail!("metadata of mint {} is immutable", mint),
+    }
+}
+
+/// Generates a keypair and, if `out_dir` is set, writes it to `<out_dir>/<label>-<pubkey>.json`
+/// and records the path in `keypair_files`.
+fn generate_keypair(
+    out_dir: Option<&Path>,
+    label: &str,
+    keypair_files: &mut Vec<PathBuf>,
+) -> Result<Keypair> {
+    let keypair = Keypair::new();
+
+    if let Some(out_dir) = out_dir {
+        fs::create_dir_all(out_dir)
+            .with_context(|| format!("failed to create {}", out_dir.display()))?;
+
+        let path = out_dir.join(format!("{}-{}.json", label, keypair.pubkey()));
+        write_keypair_file(&keypair, &path)
+            .map_err(|err| anyhow!("failed to write {}: {}", path.display(), err))?;
+        keypair_files.push(path);
+    }
+
+    Ok(keypair)
+}
@@ -1,45 +1,154 @@
-use std::env;
+use std::{env, fmt, path::PathBuf, str::FromStr};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use clap::Parser;
+use serde::Serialize;
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{
-    commitment_config::CommitmentConfig,
-    instruction::{AccountMeta, Instruction},
-    program_pack::Pack,
-    pubkey::Pubkey,
-    signature::{read_keypair_file, Keypair},
-    signer::Signer,
-    system_instruction::create_account,
-    sysvar,
-    transaction::Transaction,
+use solana_sdk::{hash::Hash, pubkey::Pubkey};
+use spl_faucet::{
+    amount::Amount,
+    batch::DEFAULT_MAX_IN_FLIGHT,
+    compute_budget::BudgetSetting,
+    config::{ClusterConfig, Config, DEFAULT_CONFIG_PATH},
+    error::Error,
+    metadata::{MetadataUpdate, TokenMetadata},
+    output::{
+        CliAirdrop, CliApplied, CliBatchAirdrop, CliClosedFaucet, CliCreatedFaucet,
+        CliCreatedNonce, CliDiscovered, CliError, CliForgotten, CliInspection, CliRegistryEntry,
+        CliRegistryList, CliSignOnly, CliUpdatedMetadata, OutputFormat,
+    },
+    registry::DEFAULT_REGISTRY_PATH,
+    signer::{Presigned, SignerConfig},
+    token::{MintExtensions, TokenProgram, TransferFee},
+    CreateOptions, FaucetClient, Sent, FAUCET_PROGRAM_ID,
 };
-use spl_token::{solana_program::pubkey, state::Mint};
-use spl_token_faucet::instruction::FaucetInstruction;
 
 #[tokio::main]
 async fn main() {
-    let Opts { global, command } = Opts::parse();
+    let Opts { global, command } = match Opts::try_parse() {
+        Ok(opts) => opts,
+        Err(err) => exit_on_usage_error(err),
+    };
+    let output = global.output;
 
-    let rpc = RpcClient::new(global.url);
-    let payer = read_keypair_file(shellexpand::tilde(&global.wallet).to_string())
-        .expect("failed to read keypair");
+    if let Err(err) = process(global, command).await {
+        let (err, message) = Error::with_message(err);
+        let error = output.formatted_string(&CliError::new(&err, message));
+        match output {
+            OutputFormat::Text => eprintln!("{}", error),
+            OutputFormat::Json | OutputFormat::JsonCompact => println!("{}", error),
+        }
+        std::process::exit(err.exit_code());
+    }
+}
 
-    match command {
-        Command::Create {
-            max_amount,
-            decimals,
-        } => inti_mint_and_faucet(decimals, payer, rpc, max_amount),
-        Command::Airdrop { .. } | Command::Close { .. } => todo!(),
+/// Exits on a command line clap rejected, reporting it as invalid input under --output json and
+/// json-compact, and through clap otherwise, which also prints --help and --version.
+fn exit_on_usage_error(err: clap::Error) -> ! {
+    let output = raw_output_format().unwrap_or(OutputFormat::Text);
+    if output == OutputFormat::Text || !err.use_stderr() {
+        err.exit();
+    }
+
+    // The error proper, without the usage and tips clap prints after it.
+    let message = err.to_string();
+    let message = message
+        .lines()
+        .take_while(|line| !line.trim().is_empty())
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join(" ");
+    let message = message
+        .strip_prefix("error: ")
+        .unwrap_or(&message)
+        .to_string();
+    let err = Error::InvalidInput(anyhow!(message.clone()));
+    println!("{}", output.formatted_string(&CliError::new(&err, message)));
+    std::process::exit(err.exit_code());
+}
+
+/// The --output format of the raw arguments, for when they fail to parse.
+fn raw_output_format() -> Option<OutputFormat> {
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if let Some(format) = arg.strip_prefix("--output=") {
+            return format.parse().ok();
+        }
+        if arg == "--output" {
+            return args.next()?.parse().ok();
+        }
     }
-    .await
-    .unwrap();
+    None
+}
+
+async fn process(global: GlobalOpts, command: Command) -> Result<()> {
+    let cluster = ClusterConfig::resolve(
+        global.url.as_deref(),
+        global.wallet.as_deref(),
+        global.commitment.as_deref(),
+        global.config.as_deref(),
+    )
+    .map_err(Error::Config)?;
+    let config =
+        Config::load(shellexpand::tilde(&global.faucet_config).as_ref()).map_err(Error::Config)?;
+    let program_id = match global.program_id {
+        Some(program_id) => program_id,
+        None => config
+            .program_id(&cluster.json_rpc_url)
+            .map_err(Error::Config)?
+            .unwrap_or(FAUCET_PROGRAM_ID),
+    };
+
+    let rpc = RpcClient::new_with_commitment(cluster.json_rpc_url, cluster.commitment);
+    let signers = SignerConfig::new(global.signers, global.sign_only);
+    let wallet = signers.signer(&cluster.keypair_path, "keypair")?;
+
+    let mut client = FaucetClient::new_with_program_id(rpc, wallet, program_id);
+    client.tx_config.allow_mainnet = global.allow_mainnet;
+    client.tx_config.dry_run = global.dry_run;
+    client.tx_config.compute_unit_limit = global.compute_unit_limit;
+    client.tx_config.priority_fee = global.priority_fee;
+    client.tx_config.nonce = global.nonce;
+    client.tx_config.sign_only = global.sign_only;
+    client.tx_config.blockhash = global.blockhash;
+    client.fee_payer = global
+        .fee_payer
+        .as_deref()
+        .map(|uri| signers.signer(uri, "fee-payer"))
+        .transpose()?;
+    client.rent_funder = global
+        .rent_funder
+        .as_deref()
+        .map(|uri| signers.signer(uri, "rent-funder"))
+        .transpose()?;
+    client.nonce_authority = global
+        .nonce_authority
+        .as_deref()
+        .map(|uri| signers.signer(uri, "nonce-authority"))
+        .transpose()?;
+
+    client.registry_path = Some(expand_path(&global.registry));
+
+    run(client, command, global.output, &signers).await
 }
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
-pub const FAUCET_PROGRAM_ID: Pubkey = pubkey!("4bXpkKSV8swHSnwqtzuboGPaPDeEgAn4Vt8GfarV5rZt");
+
+/// Exit codes of failed commands, listed in `--help`.
+const EXIT_CODES: &str = "Exit codes:
+  0  success
+  2  invalid argument, file or on-chain state
+  3  invalid Solana CLI config, faucet config or registry
+  4  unreadable signer
+  5  RPC node unreachable or failing
+  6  transaction failed
+  7  faucet program error";
 
 #[derive(Debug, Parser)]
+#[clap(after_help = EXIT_CODES)]
 struct Opts {
     #[clap(flatten)]
     pub global: GlobalOpts,
@@ -49,128 +158,522 @@ struct Opts {
 
 #[derive(Debug, Parser)]
 struct GlobalOpts {
+    /// RPC URL or moniker (localhost, devnet, testnet, mainnet-beta),
+    /// defaults to the Solana CLI config
+    #[clap(global = true, short, long, value_name = "URL_OR_MONIKER")]
+    pub url: Option<String>,
+    /// Wallet signer, defaults to the Solana CLI config; every keypair option takes a keypair
+    /// file, `file:<PATH>`, `prompt:`, `stdin:`, `env:<VAR>` or, signing offline, a pubkey
+    #[clap(global = true, short = 'k', long = "keypair")]
+    pub wallet: Option<String>,
+    /// Keypair paying the transaction fees, defaults to the keypair wallet, which stays the
+    /// faucet admin and authority
+    #[clap(global = true, long)]
+    pub fee_payer: Option<String>,
+    /// Keypair funding the rent of created accounts, defaults to the fee payer
+    #[clap(global = true, long)]
+    pub rent_funder: Option<String>,
+    /// Commitment level, defaults to the Solana CLI config
     #[clap(
         global = true,
-        short,
         long,
-        default_value = "https://api.mainnet-beta.solana.com/"
+        value_parser = ["processed", "confirmed", "finalized"]
     )]
-    pub url: String,
+    pub commitment: Option<String>,
+    /// Solana CLI config file, defaults to ~/.config/solana/cli/config.yml
+    #[clap(global = true, short = 'C', long)]
+    pub config: Option<String>,
+    /// Faucet program ID, overrides the per-cluster entries of the config file
+    #[clap(global = true, long, env = "SPL_FAUCET_PROGRAM_ID")]
+    pub program_id: Option<Pubkey>,
+    /// Path of the spl-faucet config file holding per-cluster program IDs
     #[clap(
         global = true,
-        short = 'k',
-        long = "keypair",
-        default_value = "~/.config/solana/id.json"
+        long,
+        env = "SPL_FAUCET_CONFIG",
+        default_value = DEFAULT_CONFIG_PATH
+    )]
+    pub faucet_config: String,
+    /// Path of the registry of created faucets, whose aliases are accepted wherever a faucet
+    /// address is
+    #[clap(
+        global = true,
+        long,
+        env = "SPL_FAUCET_REGISTRY",
+        default_value = DEFAULT_REGISTRY_PATH
+    )]
+    pub registry: String,
+    /// Allow sending transactions to mainnet-beta
+    #[clap(global = true, long)]
+    pub allow_mainnet: bool,
+    /// Simulate transactions instead of sending them
+    #[clap(global = true, long)]
+    pub dry_run: bool,
+    /// Compute unit limit of every transaction, `auto` to simulate and add a margin
+    #[clap(global = true, long, value_name = "UNITS|auto")]
+    pub compute_unit_limit: Option<BudgetSetting<u32>>,
+    /// Priority fee in micro-lamports per compute unit, `auto` to use recent fees
+    #[clap(global = true, long, value_name = "MICRO_LAMPORTS|auto")]
+    pub priority_fee: Option<BudgetSetting<u64>>,
+    /// Durable nonce account whose stored nonce replaces the recent blockhash, so
+    /// transactions stay valid until the nonce is advanced
+    #[clap(global = true, long, value_name = "PUBKEY")]
+    pub nonce: Option<Pubkey>,
+    /// Keypair of the authority of --nonce, defaults to the keypair wallet
+    #[clap(global = true, long, requires = "nonce")]
+    pub nonce_authority: Option<String>,
+    /// Sign the transaction without sending it, printing its message and the signatures
+    /// present; keypair options then also accept the pubkey of a signer signing elsewhere
+    #[clap(global = true, long)]
+    pub sign_only: bool,
+    /// Signature collected elsewhere, for a keypair option given as that pubkey
+    #[clap(global = true, long = "signer", value_name = "PUBKEY=SIGNATURE")]
+    pub signers: Vec<Presigned>,
+    /// Blockhash, or durable nonce value, of the transaction instead of the cluster's; with
+    /// --sign-only the transaction is built without RPC
+    #[clap(global = true, long)]
+    pub blockhash: Option<Hash>,
+    /// Output format
+    #[clap(
+        global = true,
+        long,
+        value_name = "text|json|json-compact",
+        default_value = "text"
     )]
-    pub wallet: String,
+    pub output: OutputFormat,
 }
 
 #[derive(Debug, Parser)]
 #[clap(version = VERSION)]
 enum Command {
     Create {
+        /// Airdrop limit, in UI units unless --raw is given
         #[clap(short, long)]
-        max_amount: u64,
+        max_amount: String,
         #[clap(short, long)]
         decimals: u8,
+        /// Faucet admin allowed to close the faucet and mint past the limit,
+        /// either a pubkey or `self` for the keypair wallet
+        #[clap(long, value_name = "PUBKEY|self")]
+        admin: Option<String>,
+        /// Read --max-amount and --max-fee as base units
+        #[clap(long)]
+        raw: bool,
+        /// Token program owning the mint. spl-token-2022 needs a faucet program deployment built
+        /// with Token-2022 support
+        #[clap(
+            long,
+            value_name = "spl-token|spl-token-2022",
+            default_value = "spl-token"
+        )]
+        token_program: TokenProgram,
+        /// Transfer fee in basis points, a Token-2022 extension
+        #[clap(long, value_name = "BPS", requires = "max_fee")]
+        transfer_fee_bps: Option<u16>,
+        /// Maximum transfer fee, in UI units unless --raw is given
+        #[clap(long, requires = "transfer_fee_bps")]
+        max_fee: Option<String>,
+        /// Interest rate in basis points, a Token-2022 extension
+        #[clap(long, value_name = "BPS", allow_hyphen_values = true)]
+        interest_rate: Option<i16>,
+        /// Token name, stored in a Metaplex metadata account for spl-token mints and in the
+        /// mint itself for Token-2022 mints
+        #[clap(long, requires_all = ["symbol", "uri"])]
+        name: Option<String>,
+        /// Token symbol
+        #[clap(long, requires = "name")]
+        symbol: Option<String>,
+        /// URI of the off-chain token metadata JSON
+        #[clap(long, requires = "name")]
+        uri: Option<String>,
+        /// Existing keypair for the mint, e.g. a vanity address
+        #[clap(long)]
+        mint_keypair: Option<String>,
+        /// Existing keypair for the faucet account
+        #[clap(long)]
+        faucet_keypair: Option<String>,
+        /// Directory to save generated mint and faucet keypairs to
+        #[clap(long)]
+        out_dir: Option<String>,
+        /// Name of the faucet in the registry, defaults to --symbol or the faucet address
+        #[clap(long)]
+        alias: Option<String>,
     },
     Airdrop {
         #[clap(short, long)]
         faucet: String,
+        /// Amount to mint, in UI units unless --raw is given
         #[clap(short, long)]
-        amount: u64,
+        amount: String,
+        /// Read --amount as base units
+        #[clap(long)]
+        raw: bool,
+        /// Wallet receiving the tokens, defaults to the keypair wallet
+        #[clap(short, long)]
+        recipient: Option<String>,
+    },
+    /// Airdrop to every wallet of a CSV file of `wallet,amount` rows
+    AirdropBatch {
+        #[clap(short, long)]
+        faucet: String,
+        /// Recipients, one `wallet,amount` row each with an optional header
+        #[clap(long)]
+        csv: String,
+        /// Read the amounts as base units
+        #[clap(long)]
+        raw: bool,
+        /// File recording the signature or error of every row, whose successful rows, and
+        /// failed rows whose transaction landed after all, are skipped when rerun, defaults to
+        /// `<csv>.results.csv` next to the recipients
+        #[clap(long)]
+        results: Option<String>,
+        /// Maximum number of transactions awaiting confirmation at once
+        #[clap(long, default_value_t = DEFAULT_MAX_IN_FLIGHT)]
+        max_in_flight: usize,
     },
     Close {
         #[clap(short, long)]
         faucet: String,
+        /// Account receiving the reclaimed rent, defaults to the keypair wallet
+        #[clap(short, long)]
+        destination: Option<String>,
+    },
+    /// Create the faucets of a manifest that do not exist yet
+    Apply {
+        /// TOML manifest listing the tokens by symbol
+        manifest: String,
+        /// File mapping each symbol to its mint and faucet, defaults to
+        /// `<manifest>.resolved.json` next to the manifest
+        #[clap(long)]
+        resolved: Option<String>,
+    },
+    UpdateMetadata {
+        #[clap(short, long)]
+        faucet: String,
+        /// New token name, unchanged if not given
+        #[clap(long)]
+        name: Option<String>,
+        /// New token symbol, unchanged if not given
+        #[clap(long)]
+        symbol: Option<String>,
+        /// New metadata URI, unchanged if not given
+        #[clap(long)]
+        uri: Option<String>,
+    },
+    /// Decode a faucet or a mint and its faucets, checking the mint authority is the faucet PDA
+    Inspect {
+        /// Faucet address or alias, or mint address
+        address: String,
+    },
+    /// Find the faucets of the program on-chain
+    Discover {
+        /// Only faucets of this mint
+        #[clap(long)]
+        mint: Option<Pubkey>,
+        /// Only faucets administered by this pubkey
+        #[clap(long)]
+        admin: Option<Pubkey>,
+        /// Add the faucets missing from the registry, under their address
+        #[clap(long)]
+        import: bool,
+    },
+    /// List the registered faucets of the cluster
+    List {
+        /// List the faucets of every cluster
+        #[clap(long, conflicts_with = "cluster")]
+        all: bool,
+        /// Cluster as keyed in the registry, a public cluster moniker or an RPC URL, instead of
+        /// detecting the cluster of --url
+        #[clap(long)]
+        cluster: Option<String>,
+    },
+    /// Show a registered faucet
+    Show {
+        alias: String,
+        /// Cluster as keyed in the registry, a public cluster moniker or an RPC URL, instead of
+        /// detecting the cluster of --url
+        #[clap(long)]
+        cluster: Option<String>,
+    },
+    /// Remove a faucet from the registry, leaving it on-chain
+    Forget {
+        alias: String,
+        /// Cluster as keyed in the registry, a public cluster moniker or an RPC URL, instead of
+        /// detecting the cluster of --url
+        #[clap(long)]
+        cluster: Option<String>,
+    },
+    /// Manage the durable nonce accounts passed to --nonce
+    Nonce {
+        #[clap(subcommand)]
+        command: NonceCommand,
+    },
+}
+
+#[derive(Debug, Parser)]
+enum NonceCommand {
+    /// Create a durable nonce account
+    Create {
+        /// Authority allowed to advance the nonce, defaults to the keypair wallet
+        #[clap(long)]
+        authority: Option<Pubkey>,
+        /// Keypair of the new nonce account, generated if not given
+        #[clap(long)]
+        nonce_keypair: Option<String>,
     },
 }
 
-async fn inti_mint_and_faucet(
-    decimals: u8,
-    payer: Keypair,
-    rpc: RpcClient,
-    ui_amount: u64,
+async fn run(
+    client: FaucetClient,
+    command: Command,
+    output: OutputFormat,
+    signers: &SignerConfig,
 ) -> Result<()> {
-    let mint_keypair = Keypair::new();
-    let faucet_keypair = Keypair::new();
-    let mint_authority = get_faucet_pda().0;
-
-    let amount = ui_amount * 10u64.pow(decimals as u32);
-
-    let tx = Transaction::new_signed_with_payer(
-        &[
-            create_account(
-                &payer.pubkey(),
-                &mint_keypair.pubkey(),
-                rpc.get_minimum_balance_for_rent_exemption(Mint::LEN)
-                    .await?,
-                Mint::LEN as u64,
-                &spl_token::ID,
-            ),
-            spl_token::instruction::initialize_mint2(
-                &spl_token::ID,
-                &mint_keypair.pubkey(),
-                &mint_authority,
-                None,
-                decimals,
-            )?,
-            create_account(
-                &payer.pubkey(),
-                &faucet_keypair.pubkey(),
-                rpc.get_minimum_balance_for_rent_exemption(spl_token_faucet::state::Faucet::LEN)
-                    .await?,
-                spl_token_faucet::state::Faucet::LEN as u64,
-                &FAUCET_PROGRAM_ID,
-            ),
-            create_init_faucet_ix(mint_keypair.pubkey(), faucet_keypair.pubkey(), None, amount),
-        ],
-        Some(&payer.pubkey()),
-        &[&payer, &mint_keypair, &faucet_keypair],
-        rpc.get_latest_blockhash().await?,
-    );
-
-    let sig = rpc
-        .send_and_confirm_transaction_with_spinner_and_commitment(
-            &tx,
-            CommitmentConfig::confirmed(),
-        )
-        .await?;
-
-    println!("Transaction signature: {}", sig);
-
-    println!(
-        "Mint: {}\nFaucet: {}",
-        mint_keypair.pubkey(),
-        faucet_keypair.pubkey()
-    );
+    match command {
+        Command::Create {
+            max_amount,
+            decimals,
+            admin,
+            raw,
+            token_program,
+            transfer_fee_bps,
+            max_fee,
+            interest_rate,
+            name,
+            symbol,
+            uri,
+            mint_keypair,
+            faucet_keypair,
+            out_dir,
+            alias,
+        } => {
+            let admin = match admin.as_deref() {
+                Some("self") => Some(client.wallet.pubkey()),
+                Some(admin) => Some(Pubkey::from_str(admin)?),
+                None => None,
+            };
 
-    Ok(())
-}
+            let transfer_fee = match (transfer_fee_bps, max_fee) {
+                (Some(basis_points), Some(max_fee)) => Some(TransferFee {
+                    basis_points,
+                    maximum_fee: Amount::parse(&max_fee, raw)?.to_base_units(decimals)?,
+                }),
+                _ => None,
+            };
+            let metadata = match (name, symbol, uri) {
+                (Some(name), Some(symbol), Some(uri)) => Some(TokenMetadata { name, symbol, uri }),
+                _ => None,
+            };
 
-fn get_faucet_pda() -> (Pubkey, u8) {
-    Pubkey::find_program_address(&[b"faucet"], &FAUCET_PROGRAM_ID)
-}
+            let created = client
+                .create(CreateOptions {
+                    admin,
+                    token_program,
+                    extensions: MintExtensions {
+                        transfer_fee,
+                        interest_rate,
+                        ..MintExtensions::default()
+                    },
+                    metadata,
+                    mint_keypair: mint_keypair
+                        .as_deref()
+                        .map(|uri| signers.signer(uri, "mint-keypair"))
+                        .transpose()?,
+                    faucet_keypair: faucet_keypair
+                        .as_deref()
+                        .map(|uri| signers.signer(uri, "faucet-keypair"))
+                        .transpose()?,
+                    out_dir: out_dir.as_deref().map(expand_path),
+                    alias,
+                    ..CreateOptions::new(decimals, Amount::parse(&max_amount, raw)?)
+                })
+                .await?;
 
-fn create_init_faucet_ix(
-    mint_account: Pubkey,
-    faucet_account: Pubkey,
-    admin: Option<Pubkey>,
-    amount: u64,
-) -> Instruction {
-    let mut accounts = vec![
-        AccountMeta::new_readonly(mint_account, false),
-        AccountMeta::new(faucet_account, false),
-        AccountMeta::new_readonly(sysvar::rent::id(), false),
-    ];
-
-    if let Some(admin) = admin {
-        accounts.push(AccountMeta::new_readonly(admin, false));
+            match &created.sent {
+                Sent::SignedOnly(tx) => {
+                    println!("{}", output.formatted_string(&CliSignOnly::from(tx)))
+                }
+                Sent::Signature(_) | Sent::Simulated(..) => println!(
+                    "{}",
+                    output.formatted_string(&CliCreatedFaucet::new(
+                        &created,
+                        &client.cluster().await?
+                    ))
+                ),
+            }
+        }
+        Command::Airdrop {
+            faucet,
+            amount,
+            recipient,
+            raw,
+        } => {
+            let recipient = match recipient {
+                Some(recipient) => Pubkey::from_str(&recipient)?,
+                None => client.wallet.pubkey(),
+            };
+
+            let airdrop = client
+                .airdrop(
+                    client.resolve_faucet(&faucet).await?,
+                    &Amount::parse(&amount, raw)?,
+                    recipient,
+                )
+                .await?;
+
+            print_sent(output, &airdrop.sent, || CliAirdrop::from(&airdrop));
+        }
+        Command::AirdropBatch {
+            faucet,
+            csv,
+            raw,
+            results,
+            max_in_flight,
+        } => {
+            let batch = client
+                .airdrop_batch(
+                    client.resolve_faucet(&faucet).await?,
+                    &expand_path(&csv),
+                    results.as_deref().map(expand_path),
+                    raw,
+                    max_in_flight,
+                )
+                .await?;
+
+            println!(
+                "{}",
+                output.formatted_string(&CliBatchAirdrop::from(&batch))
+            );
+        }
+        Command::Close {
+            faucet,
+            destination,
+        } => {
+            let destination = match destination {
+                Some(destination) => Pubkey::from_str(&destination)?,
+                None => client.wallet.pubkey(),
+            };
+
+            let closed = client
+                .close(client.resolve_faucet(&faucet).await?, destination)
+                .await?;
+
+            print_sent(output, &closed.sent, || CliClosedFaucet::from(&closed));
+        }
+        Command::Apply { manifest, resolved } => {
+            let applied = client
+                .apply(
+                    &expand_path(&manifest),
+                    resolved.as_deref().map(expand_path),
+                )
+                .await?;
+
+            println!("{}", output.formatted_string(&CliApplied::from(&applied)));
+        }
+        Command::UpdateMetadata {
+            faucet,
+            name,
+            symbol,
+            uri,
+        } => {
+            let updated = client
+                .update_metadata(
+                    client.resolve_faucet(&faucet).await?,
+                    MetadataUpdate { name, symbol, uri },
+                )
+                .await?;
+
+            print_sent(output, &updated.sent, || CliUpdatedMetadata::from(&updated));
+        }
+        Command::Inspect { address } => {
+            let inspection = client
+                .inspect_account(client.resolve_faucet(&address).await?)
+                .await?;
+
+            println!(
+                "{}",
+                output.formatted_string(&CliInspection::from(&inspection))
+            );
+        }
+        Command::Discover {
+            mint,
+            admin,
+            import,
+        } => {
+            let discovered = client.discover(mint, admin, import).await?;
+
+            println!(
+                "{}",
+                output.formatted_string(&CliDiscovered::from(&discovered))
+            );
+        }
+        Command::List { all, cluster } => {
+            let faucets = client
+                .registered(cluster.as_deref(), all)
+                .await?
+                .iter()
+                .map(CliRegistryEntry::from)
+                .collect();
+
+            println!("{}", output.formatted_string(&CliRegistryList { faucets }));
+        }
+        Command::Show { alias, cluster } => {
+            let registered = client.registered_faucet(&alias, cluster.as_deref()).await?;
+
+            println!(
+                "{}",
+                output.formatted_string(&CliRegistryEntry::from(&registered))
+            );
+        }
+        Command::Forget { alias, cluster } => {
+            let forgotten = client.forget(&alias, cluster.as_deref()).await?;
+
+            println!(
+                "{}",
+                output.formatted_string(&CliForgotten {
+                    alias,
+                    faucet: forgotten.entry.faucet,
+                })
+            );
+        }
+        Command::Nonce {
+            command:
+                NonceCommand::Create {
+                    authority,
+                    nonce_keypair,
+                },
+        } => {
+            let created = client
+                .create_nonce(
+                    nonce_keypair
+                        .as_deref()
+                        .map(|uri| signers.signer(uri, "nonce-keypair"))
+                        .transpose()?,
+                    authority,
+                )
+                .await?;
+
+            print_sent(output, &created.sent, || CliCreatedNonce::from(&created));
+        }
     }
 
-    Instruction {
-        program_id: FAUCET_PROGRAM_ID,
-        accounts,
-        data: FaucetInstruction::InitFaucet { amount }.pack(),
+    Ok(())
+}
+
+/// Prints the transaction signed under --sign-only, or else the `result` of the command.
+fn print_sent<T: Serialize + fmt::Display>(
+    output: OutputFormat,
+    sent: &Sent,
+    result: impl FnOnce() -> T,
+) {
+    match sent {
+        Sent::SignedOnly(tx) => println!("{}", output.formatted_string(&CliSignOnly::from(tx))),
+        Sent::Signature(_) | Sent::Simulated(..) => {
+            println!("{}", output.formatted_string(&result()))
+        }
     }
 }
+
+fn expand_path(path: &str) -> PathBuf {
+    PathBuf::from(shellexpand::tilde(path).as_ref())
+}
@@ -0,0 +1,306 @@
+//! Manifest of the faucets of a test environment, provisioned by `spl-faucet apply`, and the
+//! resolved file mapping each token to the addresses it was created at.
+//!
+//! ```toml
+//! [tokens.USDC]
+//! decimals = 6
+//! max_amount = "1000"
+//! admin = "self"
+//! metadata = { name = "USD Coin", uri = "https://example.com/usdc.json" }
+//!
+//! [tokens.wBTC]
+//! decimals = 8
+//! max_amount = 100000000
+//! raw = true
+//! token_program = "spl-token-2022"
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    amount::Amount, metadata::TokenMetadata, registry::RegistryEntry, state_file,
+    token::TokenProgram, CreateOptions,
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// Tokens keyed by symbol.
+    pub tokens: BTreeMap<String, ManifestToken>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestToken {
+    pub decimals: u8,
+    /// Airdrop limit, in UI units unless `raw` is set.
+    pub max_amount: ManifestAmount,
+    #[serde(default)]
+    pub raw: bool,
+    /// Faucet admin, either a pubkey or `self` for the wallet.
+    pub admin: Option<String>,
+    /// `spl-token` or `spl-token-2022`, defaults to `spl-token`.
+    pub token_program: Option<String>,
+    /// Metadata of the mint, whose symbol is the key of the token.
+    pub metadata: Option<ManifestMetadata>,
+}
+
+/// Amount written either as a string or as an integer.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ManifestAmount {
+    String(String),
+    Integer(u64),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestMetadata {
+    pub name: String,
+    pub uri: String,
+}
+
+impl Manifest {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        toml::from_str(&contents).with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    /// Default path of the resolved file of the manifest at `path`, `<name>.resolved.json`
+    /// next to it.
+    pub fn resolved_path(path: &Path) -> PathBuf {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        path.with_file_name(format!("{}.resolved.json", stem))
+    }
+}
+
+impl ManifestToken {
+    /// Options creating the faucet of the token `symbol`, registered under its symbol, `wallet`
+    /// standing in for an admin of `self`.
+    pub fn create_options(&self, symbol: &str, wallet: &Pubkey) -> Result<CreateOptions> {
+        let max_amount = match &self.max_amount {
+            ManifestAmount::String(amount) => amount.clone(),
+            ManifestAmount::Integer(amount) => amount.to_string(),
+        };
+        let admin = match self.admin.as_deref() {
+            Some("self") => Some(*wallet),
+            Some(admin) => Some(
+                Pubkey::from_str(admin)
+                    .map_err(|err| anyhow!("invalid admin {}: {}", admin, err))?,
+            ),
+            None => None,
+        };
+
+        Ok(CreateOptions {
+            admin,
+            token_program: self
+                .token_program
+                .as_deref()
+                .map(TokenProgram::from_str)
+                .transpose()?
+                .unwrap_or_default(),
+            metadata: self.metadata.as_ref().map(|metadata| TokenMetadata {
+                name: metadata.name.clone(),
+                symbol: symbol.to_string(),
+                uri: metadata.uri.clone(),
+            }),
+            alias: Some(symbol.to_string()),
+            ..CreateOptions::new(self.decimals, Amount::parse(&max_amount, self.raw)?)
+        })
+    }
+}
+
+/// Addresses of the tokens of a manifest, rewritten after every faucet `apply` creates.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Resolved {
+    /// Tokens keyed by symbol.
+    pub tokens: BTreeMap<String, ResolvedToken>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedToken {
+    pub mint: String,
+    pub faucet: String,
+    pub decimals: u8,
+    /// Airdrop limit in base units.
+    pub max_amount: u64,
+    pub token_program: String,
+    /// Signature of the transaction that created the faucet, unknown for faucets taken from
+    /// the registry without one.
+    pub signature: Option<String>,
+}
+
+impl ResolvedToken {
+    /// Token of the faucet registered as `entry`, whose mint is owned by `token_program`.
+    pub fn registered(entry: &RegistryEntry, token_program: TokenProgram) -> Self {
+        Self {
+            mint: entry.mint.clone(),
+            faucet: entry.faucet.clone(),
+            decimals: entry.decimals,
+            max_amount: entry.max_amount,
+            token_program: token_program.to_string(),
+            signature: entry.signature.clone(),
+        }
+    }
+}
+
+impl Resolved {
+    /// Reads the resolved file at `path`, empty if it does not exist yet.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        state_file::load_json(path.as_ref())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        state_file::save_json(path.as_ref(), self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::Registry;
+
+    const MANIFEST: &str = r#"
+        [tokens.USDC]
+        decimals = 6
+        max_amount = "1000"
+        admin = "self"
+        metadata = { name = "USD Coin", uri = "https://example.com/usdc.json" }
+
+        [tokens.wBTC]
+        decimals = 8
+        max_amount = 100000000
+        raw = true
+        token_program = "spl-token-2022"
+    "#;
+
+    fn token(toml: &str) -> Result<ManifestToken> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    #[test]
+    fn creates_faucets_of_manifest_tokens() {
+        let manifest: Manifest = toml::from_str(MANIFEST).unwrap();
+        let wallet = Pubkey::new_unique();
+
+        let usdc = manifest.tokens["USDC"]
+            .create_options("USDC", &wallet)
+            .unwrap();
+        assert_eq!(usdc.decimals, 6);
+        assert_eq!(usdc.max_amount, Amount::Ui("1000".to_string()));
+        assert_eq!(usdc.admin, Some(wallet));
+        assert_eq!(usdc.token_program, TokenProgram::SplToken);
+        assert_eq!(
+            usdc.metadata,
+            Some(TokenMetadata {
+                name: "USD Coin".to_string(),
+                symbol: "USDC".to_string(),
+                uri: "https://example.com/usdc.json".to_string(),
+            })
+        );
+        assert_eq!(usdc.alias.as_deref(), Some("USDC"));
+
+        let wbtc = manifest.tokens["wBTC"]
+            .create_options("wBTC", &wallet)
+            .unwrap();
+        assert_eq!(wbtc.max_amount, Amount::Raw(100_000_000));
+        assert_eq!(wbtc.admin, None);
+        assert_eq!(wbtc.token_program, TokenProgram::SplToken2022);
+        assert_eq!(wbtc.metadata, None);
+        assert_eq!(wbtc.alias.as_deref(), Some("wBTC"));
+    }
+
+    #[test]
+    fn takes_admins_by_pubkey() {
+        let admin = Pubkey::new_unique();
+        let token = token(&format!(
+            "decimals = 0\nmax_amount = 1\nadmin = \"{}\"",
+            admin
+        ))
+        .unwrap();
+
+        let options = token.create_options("T", &Pubkey::new_unique()).unwrap();
+        assert_eq!(options.admin, Some(admin));
+    }
+
+    #[test]
+    fn rejects_invalid_tokens() {
+        let wallet = Pubkey::new_unique();
+        for toml in [
+            "decimals = 0\nmax_amount = 1\nadmin = \"nobody\"",
+            "decimals = 0\nmax_amount = 1\ntoken_program = \"spl-token-3\"",
+            "decimals = 0\nmax_amount = \"1e3\"",
+            "decimals = 6\nmax_amount = \"1.5\"\nraw = true",
+        ] {
+            let options = token(toml).and_then(|token| token.create_options("T", &wallet));
+            assert!(options.is_err(), "{}", toml);
+        }
+
+        assert!(token("decimals = 0\nmax_amount = 1\nsymbol = \"T\"").is_err());
+        assert!(token("decimals = 0\nmax_amount = -1").is_err());
+    }
+
+    #[test]
+    fn resolves_tokens_registered_under_their_symbol() {
+        let entry = RegistryEntry {
+            symbol: Some("USDC".to_string()),
+            mint: Pubkey::new_unique().to_string(),
+            faucet: Pubkey::new_unique().to_string(),
+            decimals: 6,
+            max_amount: 1_000_000_000,
+            admin: None,
+            signature: None,
+            timestamp: 0,
+        };
+        let mut registry = Registry::default();
+        registry.insert("devnet", "USDC".to_string(), entry.clone());
+
+        let token = ResolvedToken::registered(
+            registry.get("devnet", "USDC").unwrap(),
+            TokenProgram::SplToken2022,
+        );
+        assert_eq!(token.mint, entry.mint);
+        assert_eq!(token.faucet, entry.faucet);
+        assert_eq!(token.decimals, 6);
+        assert_eq!(token.max_amount, 1_000_000_000);
+        assert_eq!(token.token_program, TokenProgram::SplToken2022.to_string());
+        assert_eq!(token.signature, None);
+        assert!(registry.get("testnet", "USDC").is_none());
+    }
+
+    #[test]
+    fn reads_resolved_tokens_with_and_without_a_signature() {
+        let resolved: Resolved = serde_json::from_str(
+            r#"{"tokens": {
+                "USDC": {"mint": "m", "faucet": "f", "decimals": 6, "maxAmount": 1,
+                         "tokenProgram": "spl-token", "signature": "s"},
+                "wBTC": {"mint": "m", "faucet": "f", "decimals": 8, "maxAmount": 1,
+                         "tokenProgram": "spl-token"}
+            }}"#,
+        )
+        .unwrap();
+
+        assert_eq!(resolved.tokens["USDC"].signature.as_deref(), Some("s"));
+        assert_eq!(resolved.tokens["wBTC"].signature, None);
+    }
+
+    #[test]
+    fn resolved_path_is_next_to_the_manifest() {
+        assert_eq!(
+            Manifest::resolved_path(Path::new("env/devnet.toml")),
+            Path::new("env/devnet.resolved.json")
+        );
+    }
+}
@@ -0,0 +1,153 @@
+//! Token metadata of faucet mints, stored in a Metaplex metadata account for spl-token mints
+//! and in the mint itself for Token-2022 mints.
+
+use anyhow::{bail, Result};
+use mpl_token_metadata::{
+    accounts::Metadata,
+    instructions::{CreateMetadataAccountV3Builder, UpdateMetadataAccountV2Builder},
+    types::DataV2,
+};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+/// Size of a Metaplex metadata account, which the program always allocates at its maximum.
+pub const METAPLEX_METADATA_LEN: usize = 679;
+
+const MAX_NAME_LEN: usize = 32;
+const MAX_SYMBOL_LEN: usize = 10;
+const MAX_URI_LEN: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+/// Fields to change in [`FaucetClient::update_metadata`](crate::FaucetClient::update_metadata),
+/// unset ones are kept.
+#[derive(Debug, Default, Clone)]
+pub struct MetadataUpdate {
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub uri: Option<String>,
+}
+
+impl TokenMetadata {
+    /// Fails if a field is longer than Metaplex allows.
+    pub fn check_metaplex_limits(&self) -> Result<()> {
+        for (field, value, max_len) in [
+            ("name", &self.name, MAX_NAME_LEN),
+            ("symbol", &self.symbol, MAX_SYMBOL_LEN),
+            ("uri", &self.uri, MAX_URI_LEN),
+        ] {
+            if value.len() > max_len {
+                bail!(
+                    "metadata {} is {} bytes long, the maximum is {}",
+                    field,
+                    value.len(),
+                    max_len
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Bytes the metadata takes in a Token-2022 mint once initialized.
+    pub fn tlv_len(&self) -> Result<usize> {
+        Ok(spl_token_metadata_interface::state::TokenMetadata {
+            name: self.name.clone(),
+            symbol: self.symbol.clone(),
+            uri: self.uri.clone(),
+            ..Default::default()
+        }
+        .tlv_size_of()?)
+    }
+
+    /// Applies `update`, returning whether anything changed.
+    pub fn apply(&mut self, update: MetadataUpdate) -> bool {
+        let before = self.clone();
+        if let Some(name) = update.name {
+            self.name = name;
+        }
+        if let Some(symbol) = update.symbol {
+            self.symbol = symbol;
+        }
+        if let Some(uri) = update.uri {
+            self.uri = uri;
+        }
+        *self != before
+    }
+
+    pub fn from_metaplex(metadata: &Metadata) -> Self {
+        // Metaplex pads the fields with NUL bytes to their maximum length.
+        Self {
+            name: metadata.name.trim_end_matches('\0').to_string(),
+            symbol: metadata.symbol.trim_end_matches('\0').to_string(),
+            uri: metadata.uri.trim_end_matches('\0').to_string(),
+        }
+    }
+
+    pub fn from_token_2022(metadata: &spl_token_metadata_interface::state::TokenMetadata) -> Self {
+        Self {
+            name: metadata.name.clone(),
+            symbol: metadata.symbol.clone(),
+            uri: metadata.uri.clone(),
+        }
+    }
+
+    fn to_metaplex_data(&self) -> DataV2 {
+        DataV2 {
+            name: self.name.clone(),
+            symbol: self.symbol.clone(),
+            uri: self.uri.clone(),
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None,
+        }
+    }
+}
+
+/// Address of the Metaplex metadata account of `mint`.
+pub fn get_metaplex_metadata_address(mint: &Pubkey) -> Pubkey {
+    Metadata::find_pda(mint).0
+}
+
+/// Creates the Metaplex metadata account of `mint`, which `mint_authority` must sign for.
+pub fn create_metaplex_metadata_ix(
+    mint: Pubkey,
+    mint_authority: Pubkey,
+    payer: Pubkey,
+    update_authority: Pubkey,
+    metadata: &TokenMetadata,
+) -> Instruction {
+    CreateMetadataAccountV3Builder::new()
+        .metadata(get_metaplex_metadata_address(&mint))
+        .mint(mint)
+        .mint_authority(mint_authority)
+        .payer(payer)
+        .update_authority(update_authority, update_authority == mint_authority)
+        .data(metadata.to_metaplex_data())
+        .is_mutable(true)
+        .instruction()
+}
+
+/// Overwrites the name, symbol and URI of the Metaplex metadata account `existing`, keeping
+/// its other fields.
+pub fn update_metaplex_metadata_ix(
+    existing: &Metadata,
+    update_authority: Pubkey,
+    metadata: &TokenMetadata,
+) -> Instruction {
+    UpdateMetadataAccountV2Builder::new()
+        .metadata(get_metaplex_metadata_address(&existing.mint))
+        .update_authority(update_authority)
+        .data(DataV2 {
+            seller_fee_basis_points: existing.seller_fee_basis_points,
+            creators: existing.creators.clone(),
+            collection: existing.collection.clone(),
+            uses: existing.uses.clone(),
+            ..metadata.to_metaplex_data()
+        })
+        .instruction()
+}
@@ -0,0 +1,1057 @@
+//! Text and JSON rendering of command results, after `solana-cli-output`.
+
+use std::{fmt, str::FromStr};
+
+use anyhow::bail;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::Serialize;
+use solana_sdk::{
+    program_option::COption, pubkey::Pubkey, signature::Signature, transaction::Transaction,
+};
+
+use crate::{
+    amount::base_units_to_ui_amount,
+    batch::BatchStatus,
+    cluster::Cluster,
+    error::Error,
+    metadata::TokenMetadata,
+    registry::{Registered, RegistryEntry},
+    token::MintExtensions,
+    Airdrop, Applied, AppliedToken, BatchAirdrop, ClosedFaucet, CreatedFaucet, CreatedNonce,
+    Discovered, FaucetInfo, Inspection, MintInfo, Sent, Simulation, UpdatedMetadata,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    JsonCompact,
+}
+
+impl OutputFormat {
+    pub fn formatted_string<T: Serialize + fmt::Display>(&self, item: &T) -> String {
+        match self {
+            Self::Text => Ok(item.to_string()),
+            Self::Json => serde_json::to_string_pretty(item),
+            Self::JsonCompact => serde_json::to_string(item),
+        }
+        // Only maps with non-string keys fail to serialize, which no output has.
+        .unwrap_or_else(|err| {
+            serde_json::json!({ "error": format!("failed to serialize the output: {}", err) })
+                .to_string()
+        })
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "json-compact" => Ok(Self::JsonCompact),
+            _ => bail!(
+                "invalid output format {}, expected text, json or json-compact",
+                s
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliCreatedFaucet {
+    pub signature: String,
+    pub mint: String,
+    pub faucet: String,
+    /// Faucet PDA, the mint authority.
+    pub pda: String,
+    pub admin: Option<String>,
+    pub token_program: String,
+    pub extensions: CliMintExtensions,
+    pub metadata: Option<CliTokenMetadata>,
+    pub decimals: u8,
+    pub max_amount: u64,
+    pub max_amount_ui: String,
+    pub cluster: String,
+    /// Registry alias of the faucet, none if not registered.
+    pub alias: Option<String>,
+    pub keypair_files: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub simulation: Option<CliSimulation>,
+}
+
+impl CliCreatedFaucet {
+    pub fn new(created: &CreatedFaucet, cluster: &Cluster) -> Self {
+        Self {
+            signature: created.sent.signature().to_string(),
+            mint: created.mint.to_string(),
+            faucet: created.faucet.to_string(),
+            pda: created.pda.to_string(),
+            admin: created.admin.map(|admin| admin.to_string()),
+            token_program: created.token_program.to_string(),
+            extensions: CliMintExtensions::new(&created.extensions, created.decimals),
+            metadata: created
+                .metadata_account
+                .as_ref()
+                .zip(created.metadata.as_ref())
+                .map(|(account, metadata)| CliTokenMetadata::new(account, metadata)),
+            decimals: created.decimals,
+            max_amount: created.max_amount,
+            max_amount_ui: base_units_to_ui_amount(created.max_amount, created.decimals),
+            cluster: cluster.key(),
+            alias: created.alias.clone(),
+            keypair_files: created
+                .keypair_files
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect(),
+            simulation: created.sent.simulation().map(CliSimulation::from),
+        }
+    }
+}
+
+impl fmt::Display for CliCreatedFaucet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for path in &self.keypair_files {
+            writeln!(f, "Wrote keypair to {}", path)?;
+        }
+        write_signature(f, &self.signature, &self.simulation)?;
+        writeln!(f, "Cluster: {}", self.cluster)?;
+        if let Some(alias) = &self.alias {
+            writeln!(f, "Registered as: {}", alias)?;
+        }
+        writeln!(f, "Mint: {}", self.mint)?;
+        writeln!(f, "Faucet: {}", self.faucet)?;
+        writeln!(f, "PDA: {}", self.pda)?;
+        writeln!(f, "Admin: {}", self.admin.as_deref().unwrap_or("none"))?;
+        writeln!(f, "Token program: {}", self.token_program)?;
+        write!(f, "{}", self.extensions)?;
+        if let Some(metadata) = &self.metadata {
+            writeln!(f, "{}", metadata)?;
+        }
+        writeln!(f, "Decimals: {}", self.decimals)?;
+        write!(
+            f,
+            "Max amount: {} ({} base units)",
+            self.max_amount_ui, self.max_amount
+        )?;
+        write_simulation(f, &self.simulation)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliMintExtensions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transfer_fee: Option<CliTransferFee>,
+    /// Interest rate in basis points.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interest_rate: Option<i16>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliTransferFee {
+    pub basis_points: u16,
+    pub maximum_fee: u64,
+    pub maximum_fee_ui: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliTokenMetadata {
+    /// Metaplex metadata account, or the mint itself for Token-2022 mints.
+    pub account: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+impl CliTokenMetadata {
+    pub fn new(account: &Pubkey, metadata: &TokenMetadata) -> Self {
+        Self {
+            account: account.to_string(),
+            name: metadata.name.clone(),
+            symbol: metadata.symbol.clone(),
+            uri: metadata.uri.clone(),
+        }
+    }
+}
+
+impl fmt::Display for CliTokenMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Metadata account: {}", self.account)?;
+        writeln!(f, "Name: {}", self.name)?;
+        writeln!(f, "Symbol: {}", self.symbol)?;
+        write!(f, "URI: {}", self.uri)
+    }
+}
+
+impl CliMintExtensions {
+    pub fn new(extensions: &MintExtensions, decimals: u8) -> Self {
+        Self {
+            transfer_fee: extensions.transfer_fee.map(|transfer_fee| CliTransferFee {
+                basis_points: transfer_fee.basis_points,
+                maximum_fee: transfer_fee.maximum_fee,
+                maximum_fee_ui: base_units_to_ui_amount(transfer_fee.maximum_fee, decimals),
+            }),
+            interest_rate: extensions.interest_rate,
+        }
+    }
+}
+
+/// One line per extension, nothing without extensions.
+impl fmt::Display for CliMintExtensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(transfer_fee) = &self.transfer_fee {
+            writeln!(
+                f,
+                "Transfer fee: {} bps, at most {} ({} base units)",
+                transfer_fee.basis_points, transfer_fee.maximum_fee_ui, transfer_fee.maximum_fee
+            )?;
+        }
+        if let Some(interest_rate) = self.interest_rate {
+            writeln!(f, "Interest rate: {} bps", interest_rate)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliAirdrop {
+    pub signature: String,
+    pub token_account: String,
+    pub balance: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub simulation: Option<CliSimulation>,
+}
+
+impl From<&Airdrop> for CliAirdrop {
+    fn from(airdrop: &Airdrop) -> Self {
+        Self {
+            signature: airdrop.sent.signature().to_string(),
+            token_account: airdrop.token_account.to_string(),
+            balance: airdrop.balance.clone(),
+            simulation: airdrop.sent.simulation().map(CliSimulation::from),
+        }
+    }
+}
+
+impl fmt::Display for CliAirdrop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_signature(f, &self.signature, &self.simulation)?;
+        write!(f, "Token account: {}", self.token_account)?;
+        if let Some(balance) = &self.balance {
+            write!(f, "\nBalance: {}", balance)?;
+        }
+        write_simulation(f, &self.simulation)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliBatchAirdrop {
+    pub results_file: String,
+    pub ok: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub simulated: usize,
+    pub rows: Vec<CliBatchRow>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliBatchRow {
+    pub line: u64,
+    pub wallet: String,
+    pub amount: String,
+    /// `ok`, `failed`, `skipped` or, on a dry run, `simulated`.
+    pub status: String,
+    pub signature: Option<String>,
+    pub error: Option<String>,
+}
+
+impl From<&BatchAirdrop> for CliBatchAirdrop {
+    fn from(batch: &BatchAirdrop) -> Self {
+        let results = &batch.results;
+        let count = |status| {
+            results
+                .iter()
+                .filter(|result| result.status == status)
+                .count()
+        };
+
+        Self {
+            results_file: batch.results_path.display().to_string(),
+            ok: count(BatchStatus::Ok),
+            failed: count(BatchStatus::Failed),
+            skipped: count(BatchStatus::Skipped),
+            simulated: count(BatchStatus::Simulated),
+            rows: results
+                .iter()
+                .map(|result| CliBatchRow {
+                    line: result.line,
+                    wallet: result.wallet.clone(),
+                    amount: result.amount.clone(),
+                    status: result.status.to_string(),
+                    signature: result.signature.clone(),
+                    error: result.error.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for CliBatchAirdrop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<6}  {:<9}  {:<44}  Amount",
+            "Line", "Status", "Wallet"
+        )?;
+        for row in &self.rows {
+            writeln!(
+                f,
+                "{:<6}  {:<9}  {:<44}  {}",
+                row.line, row.status, row.wallet, row.amount
+            )?;
+        }
+        if self.rows.iter().any(|row| row.error.is_some()) {
+            writeln!(f)?;
+        }
+        for row in &self.rows {
+            if let Some(error) = &row.error {
+                writeln!(f, "Line {}: {}", row.line, error)?;
+            }
+        }
+
+        write!(
+            f,
+            "\n{} ok, {} failed, {} skipped",
+            self.ok, self.failed, self.skipped
+        )?;
+        if self.simulated > 0 {
+            write!(
+                f,
+                ", {} simulated\nDry run, {} not written",
+                self.simulated, self.results_file
+            )
+        } else {
+            write!(f, "\nResults written to {}", self.results_file)
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliClosedFaucet {
+    pub signature: String,
+    pub faucet: String,
+    pub destination: String,
+    pub lamports: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub simulation: Option<CliSimulation>,
+}
+
+impl From<&ClosedFaucet> for CliClosedFaucet {
+    fn from(closed: &ClosedFaucet) -> Self {
+        Self {
+            signature: closed.sent.signature().to_string(),
+            faucet: closed.faucet.to_string(),
+            destination: closed.destination.to_string(),
+            lamports: closed.lamports,
+            simulation: closed.sent.simulation().map(CliSimulation::from),
+        }
+    }
+}
+
+impl fmt::Display for CliClosedFaucet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_signature(f, &self.signature, &self.simulation)?;
+        write!(
+            f,
+            "Closed faucet {}, reclaimed {} lamports to {}",
+            self.faucet, self.lamports, self.destination
+        )?;
+        write_simulation(f, &self.simulation)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliSignOnly {
+    pub blockhash: String,
+    /// Serialized message, base64-encoded.
+    pub message: String,
+    /// Signatures present, as `<PUBKEY>=<SIGNATURE>`.
+    pub signers: Vec<String>,
+    pub absent: Vec<String>,
+}
+
+impl From<&Transaction> for CliSignOnly {
+    fn from(tx: &Transaction) -> Self {
+        // Signers in signing order, split by whether their signature is present.
+        let (present, absent): (Vec<_>, Vec<_>) = tx
+            .message
+            .account_keys
+            .iter()
+            .zip(&tx.signatures)
+            .partition(|(_, signature)| **signature != Signature::default());
+        Self {
+            blockhash: tx.message.recent_blockhash.to_string(),
+            message: BASE64.encode(tx.message_data()),
+            signers: present
+                .iter()
+                .map(|(pubkey, signature)| format!("{}={}", pubkey, signature))
+                .collect(),
+            absent: absent
+                .iter()
+                .map(|(pubkey, _)| pubkey.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for CliSignOnly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Blockhash: {}", self.blockhash)?;
+        write!(f, "Message: {}", self.message)?;
+        if !self.signers.is_empty() {
+            write!(f, "\nSigners (Pubkey=Signature):")?;
+            for signer in &self.signers {
+                write!(f, "\n  {}", signer)?;
+            }
+        }
+        if !self.absent.is_empty() {
+            write!(f, "\nAbsent Signers (Pubkey):")?;
+            for pubkey in &self.absent {
+                write!(f, "\n  {}", pubkey)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliCreatedNonce {
+    pub signature: String,
+    pub nonce: String,
+    pub authority: String,
+    pub lamports: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub simulation: Option<CliSimulation>,
+}
+
+impl From<&CreatedNonce> for CliCreatedNonce {
+    fn from(created: &CreatedNonce) -> Self {
+        Self {
+            signature: created.sent.signature().to_string(),
+            nonce: created.nonce.to_string(),
+            authority: created.authority.to_string(),
+            lamports: created.lamports,
+            simulation: created.sent.simulation().map(CliSimulation::from),
+        }
+    }
+}
+
+impl fmt::Display for CliCreatedNonce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_signature(f, &self.signature, &self.simulation)?;
+        writeln!(f, "Nonce account: {}", self.nonce)?;
+        write!(f, "Authority: {}", self.authority)?;
+        write_simulation(f, &self.simulation)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliUpdatedMetadata {
+    pub signature: String,
+    pub mint: String,
+    pub metadata: CliTokenMetadata,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub simulation: Option<CliSimulation>,
+}
+
+impl From<&UpdatedMetadata> for CliUpdatedMetadata {
+    fn from(updated: &UpdatedMetadata) -> Self {
+        Self {
+            signature: updated.sent.signature().to_string(),
+            mint: updated.mint.to_string(),
+            metadata: CliTokenMetadata::new(&updated.metadata_account, &updated.metadata),
+            simulation: updated.sent.simulation().map(CliSimulation::from),
+        }
+    }
+}
+
+impl fmt::Display for CliUpdatedMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_signature(f, &self.signature, &self.simulation)?;
+        writeln!(f, "Mint: {}", self.mint)?;
+        write!(f, "{}", self.metadata)?;
+        write_simulation(f, &self.simulation)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliApplied {
+    pub resolved_file: String,
+    pub tokens: Vec<CliAppliedToken>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliAppliedToken {
+    pub symbol: String,
+    /// `existing`, `created` or, on a dry run, `simulated`.
+    pub status: String,
+    pub mint: String,
+    pub faucet: String,
+    /// Unknown for existing faucets taken from the registry without one.
+    pub signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub simulation: Option<CliSimulation>,
+}
+
+impl From<&Applied> for CliApplied {
+    fn from(applied: &Applied) -> Self {
+        Self {
+            resolved_file: applied.resolved_path.display().to_string(),
+            tokens: applied
+                .tokens
+                .iter()
+                .map(|token| match token {
+                    AppliedToken::Existing { symbol, token } => CliAppliedToken {
+                        symbol: symbol.clone(),
+                        status: "existing".to_string(),
+                        mint: token.mint.clone(),
+                        faucet: token.faucet.clone(),
+                        signature: token.signature.clone(),
+                        simulation: None,
+                    },
+                    AppliedToken::Created { symbol, created } => CliAppliedToken {
+                        symbol: symbol.clone(),
+                        // Apply never signs only.
+                        status: match created.sent {
+                            Sent::Signature(_) => "created",
+                            Sent::Simulated(..) | Sent::SignedOnly(_) => "simulated",
+                        }
+                        .to_string(),
+                        mint: created.mint.to_string(),
+                        faucet: created.faucet.to_string(),
+                        signature: Some(created.sent.signature().to_string()),
+                        simulation: created.sent.simulation().map(CliSimulation::from),
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for CliApplied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol_width = self
+            .tokens
+            .iter()
+            .map(|token| token.symbol.len())
+            .max()
+            .unwrap_or_default()
+            .max("Symbol".len());
+
+        writeln!(
+            f,
+            "{:<symbol_width$}  {:<9}  {:<44}  Faucet",
+            "Symbol", "Status", "Mint"
+        )?;
+        for token in &self.tokens {
+            writeln!(
+                f,
+                "{:<symbol_width$}  {:<9}  {:<44}  {}",
+                token.symbol, token.status, token.mint, token.faucet
+            )?;
+        }
+        for token in &self.tokens {
+            if let Some(simulation) = &token.simulation {
+                writeln!(f, "\n{}:\n{}", token.symbol, simulation)?;
+            }
+        }
+        if self.tokens.iter().any(|token| token.simulation.is_some()) {
+            write!(f, "\nDry run, {} not written", self.resolved_file)
+        } else {
+            write!(f, "\nResolved addresses written to {}", self.resolved_file)
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliInspection {
+    pub mint: CliMint,
+    pub faucets: Vec<CliFaucet>,
+    /// Faucet PDA, the expected mint authority.
+    pub pda: String,
+    pub mint_authority_is_pda: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliMint {
+    pub address: String,
+    pub token_program: String,
+    pub decimals: u8,
+    pub supply: u64,
+    pub supply_ui: String,
+    pub mint_authority: Option<String>,
+    pub freeze_authority: Option<String>,
+    pub extensions: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliFaucet {
+    pub address: String,
+    pub admin: Option<String>,
+    pub max_amount: u64,
+    pub max_amount_ui: String,
+}
+
+impl From<&Inspection> for CliInspection {
+    fn from(inspection: &Inspection) -> Self {
+        let MintInfo {
+            address,
+            mint,
+            token_program,
+            extensions,
+        } = &inspection.mint;
+
+        Self {
+            mint: CliMint {
+                address: address.to_string(),
+                token_program: token_program.to_string(),
+                decimals: mint.decimals,
+                supply: mint.supply,
+                supply_ui: base_units_to_ui_amount(mint.supply, mint.decimals),
+                mint_authority: Option::from(mint.mint_authority)
+                    .map(|authority: Pubkey| authority.to_string()),
+                freeze_authority: Option::from(mint.freeze_authority)
+                    .map(|authority: Pubkey| authority.to_string()),
+                extensions: extensions
+                    .iter()
+                    .map(|extension| format!("{:?}", extension))
+                    .collect(),
+            },
+            faucets: inspection
+                .faucets
+                .iter()
+                .map(|(address, faucet)| CliFaucet {
+                    address: address.to_string(),
+                    admin: Option::from(faucet.admin).map(|admin: Pubkey| admin.to_string()),
+                    max_amount: faucet.amount,
+                    max_amount_ui: base_units_to_ui_amount(faucet.amount, mint.decimals),
+                })
+                .collect(),
+            pda: inspection.pda.to_string(),
+            mint_authority_is_pda: mint.mint_authority == COption::Some(inspection.pda),
+        }
+    }
+}
+
+impl fmt::Display for CliInspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mint = &self.mint;
+        writeln!(f, "Mint              {}", mint.address)?;
+        writeln!(f, "Token program     {}", mint.token_program)?;
+        writeln!(f, "Decimals          {}", mint.decimals)?;
+        writeln!(
+            f,
+            "Supply            {} ({} base units)",
+            mint.supply_ui, mint.supply
+        )?;
+        if self.mint_authority_is_pda {
+            writeln!(f, "Mint authority    {} (faucet PDA)", self.pda)?;
+        } else {
+            writeln!(
+                f,
+                "Mint authority    {}, NOT the faucet PDA {}",
+                mint.mint_authority.as_deref().unwrap_or("none"),
+                self.pda
+            )?;
+        }
+        writeln!(
+            f,
+            "Freeze authority  {}",
+            mint.freeze_authority.as_deref().unwrap_or("none")
+        )?;
+        if !mint.extensions.is_empty() {
+            writeln!(f, "Extensions        {}", mint.extensions.join(", "))?;
+        }
+
+        if self.faucets.is_empty() {
+            return write!(f, "Faucets           none found");
+        }
+        for (i, faucet) in self.faucets.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "Faucet            {}", faucet.address)?;
+            writeln!(
+                f,
+                "  Admin           {}",
+                faucet.admin.as_deref().unwrap_or("none")
+            )?;
+            write!(
+                f,
+                "  Max amount      {} ({} base units)",
+                faucet.max_amount_ui, faucet.max_amount
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliDiscovered {
+    pub faucets: Vec<CliDiscoveredFaucet>,
+    /// Aliases the faucets were imported into the registry under.
+    pub imported: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliDiscoveredFaucet {
+    pub address: String,
+    pub mint: String,
+    pub token_program: String,
+    pub admin: Option<String>,
+    pub decimals: u8,
+    pub max_amount: u64,
+    pub max_amount_ui: String,
+    /// Registry alias of the faucet, if any.
+    pub alias: Option<String>,
+}
+
+impl CliDiscoveredFaucet {
+    pub fn new(info: &FaucetInfo, alias: Option<String>) -> Self {
+        Self {
+            address: info.address.to_string(),
+            mint: info.faucet.mint.to_string(),
+            token_program: info.token_program.to_string(),
+            admin: Option::from(info.faucet.admin).map(|admin: Pubkey| admin.to_string()),
+            decimals: info.mint.decimals,
+            max_amount: info.faucet.amount,
+            max_amount_ui: base_units_to_ui_amount(info.faucet.amount, info.mint.decimals),
+            alias,
+        }
+    }
+}
+
+impl From<&Discovered> for CliDiscovered {
+    fn from(discovered: &Discovered) -> Self {
+        Self {
+            faucets: discovered
+                .faucets
+                .iter()
+                .map(|(info, alias)| CliDiscoveredFaucet::new(info, alias.clone()))
+                .collect(),
+            imported: discovered.imported.clone(),
+        }
+    }
+}
+
+impl fmt::Display for CliDiscovered {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.faucets.is_empty() {
+            return write!(f, "No faucets found");
+        }
+
+        write!(
+            f,
+            "{:<44}  {:<44}  {:<44}  Max amount",
+            "Faucet", "Mint", "Admin"
+        )?;
+        for faucet in &self.faucets {
+            write!(
+                f,
+                "\n{:<44}  {:<44}  {:<44}  {}",
+                faucet.address,
+                faucet.mint,
+                faucet.admin.as_deref().unwrap_or("none"),
+                faucet.max_amount_ui
+            )?;
+            if let Some(alias) = &faucet.alias {
+                write!(f, " ({})", alias)?;
+            }
+        }
+        if !self.imported.is_empty() {
+            write!(
+                f,
+                "\n\nImported {} faucets into the registry",
+                self.imported.len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliRegistryEntry {
+    pub alias: String,
+    pub cluster: String,
+    #[serde(flatten)]
+    pub entry: RegistryEntry,
+}
+
+impl From<&Registered> for CliRegistryEntry {
+    fn from(registered: &Registered) -> Self {
+        Self {
+            alias: registered.alias.clone(),
+            cluster: registered.cluster.clone(),
+            entry: registered.entry.clone(),
+        }
+    }
+}
+
+impl fmt::Display for CliRegistryEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entry = &self.entry;
+        writeln!(f, "Alias: {}", self.alias)?;
+        writeln!(f, "Cluster: {}", self.cluster)?;
+        writeln!(f, "Symbol: {}", entry.symbol.as_deref().unwrap_or("none"))?;
+        writeln!(f, "Mint: {}", entry.mint)?;
+        writeln!(f, "Faucet: {}", entry.faucet)?;
+        writeln!(f, "Admin: {}", entry.admin.as_deref().unwrap_or("none"))?;
+        writeln!(f, "Decimals: {}", entry.decimals)?;
+        writeln!(
+            f,
+            "Max amount: {} ({} base units)",
+            base_units_to_ui_amount(entry.max_amount, entry.decimals),
+            entry.max_amount
+        )?;
+        writeln!(
+            f,
+            "Signature: {}",
+            entry.signature.as_deref().unwrap_or("unknown")
+        )?;
+        write!(f, "Registered at: {} (Unix time)", entry.timestamp)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliRegistryList {
+    pub faucets: Vec<CliRegistryEntry>,
+}
+
+impl fmt::Display for CliRegistryList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.faucets.is_empty() {
+            return write!(f, "No faucets registered");
+        }
+
+        let width = |column: &str, value: fn(&CliRegistryEntry) -> &str| {
+            self.faucets
+                .iter()
+                .map(|faucet| value(faucet).len())
+                .max()
+                .unwrap_or_default()
+                .max(column.len())
+        };
+        let cluster_width = width("Cluster", |faucet| &faucet.cluster);
+        let alias_width = width("Alias", |faucet| &faucet.alias);
+        let symbol_width = width("Symbol", |faucet| {
+            faucet.entry.symbol.as_deref().unwrap_or_default()
+        });
+
+        write!(
+            f,
+            "{:<cluster_width$}  {:<alias_width$}  {:<symbol_width$}  {:<44}  Max amount",
+            "Cluster", "Alias", "Symbol", "Faucet"
+        )?;
+        for faucet in &self.faucets {
+            write!(
+                f,
+                "\n{:<cluster_width$}  {:<alias_width$}  {:<symbol_width$}  {:<44}  {}",
+                faucet.cluster,
+                faucet.alias,
+                faucet.entry.symbol.as_deref().unwrap_or_default(),
+                faucet.entry.faucet,
+                base_units_to_ui_amount(faucet.entry.max_amount, faucet.entry.decimals)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliForgotten {
+    pub alias: String,
+    pub faucet: String,
+}
+
+impl fmt::Display for CliForgotten {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Removed {} (faucet {}) from the registry",
+            self.alias, self.faucet
+        )
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliSimulation {
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+    pub rent: u64,
+    pub fee: u64,
+}
+
+impl From<&Simulation> for CliSimulation {
+    fn from(simulation: &Simulation) -> Self {
+        Self {
+            logs: simulation.logs.clone(),
+            units_consumed: simulation.units_consumed,
+            rent: simulation.rent,
+            fee: simulation.fee,
+        }
+    }
+}
+
+impl fmt::Display for CliSimulation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Simulation logs:")?;
+        for log in &self.logs {
+            writeln!(f, "  {}", log)?;
+        }
+        if let Some(units_consumed) = self.units_consumed {
+            writeln!(f, "Compute units: {}", units_consumed)?;
+        }
+        writeln!(f, "Rent: {} lamports", self.rent)?;
+        write!(f, "Fee: {} lamports", self.fee)
+    }
+}
+
+fn write_signature(
+    f: &mut fmt::Formatter<'_>,
+    signature: &str,
+    simulation: &Option<CliSimulation>,
+) -> fmt::Result {
+    match simulation {
+        Some(_) => writeln!(f, "Dry run, transaction {} not sent", signature),
+        None => writeln!(f, "Transaction signature: {}", signature),
+    }
+}
+
+fn write_simulation(f: &mut fmt::Formatter<'_>, simulation: &Option<CliSimulation>) -> fmt::Result {
+    match simulation {
+        Some(simulation) => write!(f, "\n{}", simulation),
+        None => Ok(()),
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliError {
+    pub error: String,
+    pub kind: &'static str,
+    pub exit_code: i32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub logs: Vec<String>,
+}
+
+impl CliError {
+    /// Error `err`, described by `message`.
+    pub fn new(err: &Error, message: String) -> Self {
+        Self {
+            error: message,
+            kind: err.kind(),
+            exit_code: err.exit_code(),
+            logs: err.logs().to_vec(),
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Error: {}", self.error)?;
+        for log in &self.logs {
+            write!(f, "\n  {}", log)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+    use serde_json::json;
+
+    use super::*;
+    use crate::token::TokenProgram;
+
+    #[test]
+    fn serializes_created_faucets_in_camel_case() {
+        let signature = Signature::new_unique();
+        let created = CreatedFaucet {
+            mint: Pubkey::new_unique(),
+            faucet: Pubkey::new_unique(),
+            pda: Pubkey::new_unique(),
+            admin: None,
+            token_program: TokenProgram::SplToken,
+            extensions: MintExtensions::default(),
+            metadata: None,
+            metadata_account: None,
+            decimals: 6,
+            max_amount: 1_500_000,
+            keypair_files: vec![],
+            alias: None,
+            sent: Sent::Signature(signature),
+        };
+
+        let value =
+            serde_json::to_value(CliCreatedFaucet::new(&created, &Cluster::Devnet)).unwrap();
+        assert_eq!(value["signature"], json!(signature.to_string()));
+        assert_eq!(value["mint"], json!(created.mint.to_string()));
+        assert_eq!(value["faucet"], json!(created.faucet.to_string()));
+        assert_eq!(value["pda"], json!(created.pda.to_string()));
+        assert_eq!(value["decimals"], json!(6));
+        assert_eq!(value["maxAmount"], json!(1_500_000));
+        assert_eq!(value["maxAmountUi"], json!("1.5"));
+        assert_eq!(value["cluster"], json!("devnet"));
+        assert_eq!(value["tokenProgram"], json!("spl-token"));
+        assert!(value.get("simulation").is_none());
+    }
+
+    #[test]
+    fn serializes_errors_with_their_kind_and_exit_code() {
+        let err = Error::InvalidInput(anyhow!("no faucet named usdc"));
+        let value =
+            serde_json::to_value(CliError::new(&err, "no faucet named usdc".to_string())).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "error": "no faucet named usdc",
+                "kind": "invalid-input",
+                "exitCode": 2,
+            })
+        );
+
+        let err = Error::FaucetProgram {
+            code: 1,
+            logs: vec!["Program log: Error".to_string()],
+        };
+        let value = serde_json::to_value(CliError::new(&err, err.to_string())).unwrap();
+        assert_eq!(value["kind"], json!("faucet-program"));
+        assert_eq!(value["exitCode"], json!(7));
+        assert_eq!(value["logs"], json!(["Program log: Error"]));
+    }
+}
@@ -0,0 +1,227 @@
+//! Local registry of the faucets created by `spl-faucet`, per cluster and keyed by alias,
+//! `~/.config/spl-faucet/registry.json` by default.
+
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{state_file, CreatedFaucet, FaucetInfo};
+
+pub const DEFAULT_REGISTRY_PATH: &str = "~/.config/spl-faucet/registry.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Registry {
+    /// Faucets keyed by [`Cluster::key`](crate::cluster::Cluster::key), then by alias.
+    #[serde(default)]
+    pub clusters: BTreeMap<String, BTreeMap<String, RegistryEntry>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistryEntry {
+    pub symbol: Option<String>,
+    pub mint: String,
+    pub faucet: String,
+    pub decimals: u8,
+    /// Airdrop limit in base units.
+    pub max_amount: u64,
+    pub admin: Option<String>,
+    /// Signature of the transaction that created the faucet, unknown for discovered faucets.
+    pub signature: Option<String>,
+    /// Creation or import time in seconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+/// Registry entry along with the cluster and alias it is recorded under.
+#[derive(Debug, Clone)]
+pub struct Registered {
+    /// Key of the cluster, see [`Registry::clusters`].
+    pub cluster: String,
+    pub alias: String,
+    pub entry: RegistryEntry,
+}
+
+impl Registered {
+    pub fn new(cluster: &str, alias: &str, entry: &RegistryEntry) -> Self {
+        Self {
+            cluster: cluster.to_string(),
+            alias: alias.to_string(),
+            entry: entry.clone(),
+        }
+    }
+}
+
+impl RegistryEntry {
+    pub fn new(created: &CreatedFaucet) -> Self {
+        Self {
+            symbol: created
+                .metadata
+                .as_ref()
+                .map(|metadata| metadata.symbol.clone()),
+            mint: created.mint.to_string(),
+            faucet: created.faucet.to_string(),
+            decimals: created.decimals,
+            max_amount: created.max_amount,
+            admin: created.admin.map(|admin| admin.to_string()),
+            signature: Some(created.sent.signature().to_string()),
+            timestamp: now(),
+        }
+    }
+
+    /// Entry of a faucet found on-chain rather than created by this tool.
+    pub fn discovered(info: &FaucetInfo) -> Self {
+        Self {
+            symbol: None,
+            mint: info.faucet.mint.to_string(),
+            faucet: info.address.to_string(),
+            decimals: info.mint.decimals,
+            max_amount: info.faucet.amount,
+            admin: Option::from(info.faucet.admin).map(|admin: Pubkey| admin.to_string()),
+            signature: None,
+            timestamp: now(),
+        }
+    }
+}
+
+impl Registry {
+    /// Reads the registry at `path`, empty if it does not exist yet.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        state_file::load_json(path.as_ref())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        state_file::save_json(path.as_ref(), self)
+    }
+
+    /// Faucets of every cluster.
+    pub fn all(&self) -> impl Iterator<Item = Registered> + '_ {
+        self.clusters.iter().flat_map(|(cluster, entries)| {
+            entries
+                .iter()
+                .map(move |(alias, entry)| Registered::new(cluster, alias, entry))
+        })
+    }
+
+    /// Faucets of the cluster keyed `cluster` by alias.
+    pub fn entries(&self, cluster: &str) -> impl Iterator<Item = (&String, &RegistryEntry)> {
+        self.clusters.get(cluster).into_iter().flatten()
+    }
+
+    pub fn get(&self, cluster: &str, alias: &str) -> Option<&RegistryEntry> {
+        self.clusters.get(cluster)?.get(alias)
+    }
+
+    /// Records `entry` as `alias`, replacing any faucet already registered under it.
+    pub fn insert(&mut self, cluster: &str, alias: String, entry: RegistryEntry) {
+        self.clusters
+            .entry(cluster.to_string())
+            .or_default()
+            .insert(alias, entry);
+    }
+
+    pub fn remove(&mut self, cluster: &str, alias: &str) -> Result<RegistryEntry> {
+        let entries = self.clusters.get_mut(cluster);
+        let entry = entries
+            .and_then(|entries| entries.remove(alias))
+            .ok_or_else(|| anyhow!("no faucet named {} on {}", alias, cluster))?;
+
+        self.clusters.retain(|_, entries| !entries.is_empty());
+        Ok(entry)
+    }
+
+    /// Alias of `faucet` on `cluster`, if registered.
+    pub fn alias_of(&self, cluster: &str, faucet: &Pubkey) -> Option<&String> {
+        let faucet = faucet.to_string();
+        self.entries(cluster)
+            .find(|(_, entry)| entry.faucet == faucet)
+            .map(|(alias, _)| alias)
+    }
+
+    /// Faucet address given either as a pubkey or as an alias registered on `cluster`.
+    pub fn resolve_faucet(&self, cluster: &str, faucet: &str) -> Result<Pubkey> {
+        if let Ok(faucet) = Pubkey::from_str(faucet) {
+            return Ok(faucet);
+        }
+        match self.get(cluster, faucet) {
+            Some(entry) => Pubkey::from_str(&entry.faucet)
+                .with_context(|| format!("invalid faucet {} in the registry", entry.faucet)),
+            None => bail!(
+                "{} is neither a faucet address nor an alias registered on {}",
+                faucet,
+                cluster
+            ),
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry() -> RegistryEntry {
+        RegistryEntry {
+            symbol: None,
+            mint: Pubkey::new_unique().to_string(),
+            faucet: Pubkey::new_unique().to_string(),
+            decimals: 6,
+            max_amount: 1_000_000,
+            admin: None,
+            signature: None,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn removes_entries_and_drops_empty_clusters() {
+        let mut registry = Registry::default();
+        registry.insert("devnet", "usdc".to_string(), entry());
+        registry.insert("devnet", "wbtc".to_string(), entry());
+        registry.insert("testnet", "usdc".to_string(), entry());
+
+        registry.remove("devnet", "usdc").unwrap();
+        assert!(registry.get("devnet", "usdc").is_none());
+        assert!(registry.get("devnet", "wbtc").is_some());
+
+        registry.remove("testnet", "usdc").unwrap();
+        assert_eq!(registry.clusters.keys().collect::<Vec<_>>(), ["devnet"]);
+        assert!(registry.remove("testnet", "usdc").is_err());
+        assert!(!registry.clusters.contains_key("testnet"));
+    }
+
+    #[test]
+    fn resolves_faucets_by_address_or_alias() {
+        let mut registry = Registry::default();
+        let entry = entry();
+        registry.insert("devnet", "usdc".to_string(), entry.clone());
+
+        assert_eq!(
+            registry
+                .resolve_faucet("devnet", "usdc")
+                .unwrap()
+                .to_string(),
+            entry.faucet
+        );
+        assert_eq!(
+            registry
+                .resolve_faucet("testnet", &entry.faucet)
+                .unwrap()
+                .to_string(),
+            entry.faucet
+        );
+        assert!(registry.resolve_faucet("testnet", "usdc").is_err());
+    }
+}
@@ -0,0 +1,359 @@
+//! Signers of the keypair-like options, given as signer URIs:
+//!
+//! - a keypair file path, or `file:<PATH>`
+//! - `prompt:`, asking for a seed phrase and an optional BIP39 passphrase
+//! - `stdin:`, reading a JSON keypair from standard input, for at most one option
+//! - `env:<VAR>`, a JSON keypair or base58 secret key held by an environment variable
+//! - a pubkey, whose signature is collected offline, mirroring the `solana` CLI `--sign-only`
+//!   and `--signer` flow
+//!
+//! Hardware wallets (`usb://`) are not supported.
+
+use std::{cell::RefCell, env, fmt, io, str::FromStr};
+
+use anyhow::{anyhow, bail, Context, Result};
+use bip39::{Language, Mnemonic};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{
+        keypair_from_seed_phrase_and_passphrase, read_keypair, read_keypair_file, Keypair,
+        Signature,
+    },
+    signer::{null_signer::NullSigner, presigner::Presigner, Signer},
+};
+
+use crate::error::Error;
+
+/// Signature of `pubkey` collected elsewhere, written `<PUBKEY>=<SIGNATURE>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Presigned {
+    pub pubkey: Pubkey,
+    pub signature: Signature,
+}
+
+impl FromStr for Presigned {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (pubkey, signature) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow!("invalid signer {}, expected <PUBKEY>=<SIGNATURE>", s))?;
+
+        Ok(Self {
+            pubkey: Pubkey::from_str(pubkey)
+                .map_err(|err| anyhow!("invalid signer pubkey {}: {}", pubkey, err))?,
+            signature: Signature::from_str(signature)
+                .map_err(|err| anyhow!("invalid signature {}: {}", signature, err))?,
+        })
+    }
+}
+
+impl fmt::Display for Presigned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.pubkey, self.signature)
+    }
+}
+
+/// Where a signer comes from, parsed from a signer URI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignerSource {
+    File(String),
+    Prompt,
+    Stdin,
+    Env(String),
+    Pubkey(Pubkey),
+}
+
+impl FromStr for SignerSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("usb://") {
+            bail!(
+                "hardware wallet {} is not supported, use a keypair file, prompt:, stdin: or \
+                 env:<VAR>",
+                s
+            );
+        }
+        if let Ok(pubkey) = Pubkey::from_str(s) {
+            return Ok(Self::Pubkey(pubkey));
+        }
+
+        Ok(match s {
+            "prompt:" | "prompt://" => Self::Prompt,
+            "stdin:" | "stdin://" => Self::Stdin,
+            _ => match (s.strip_prefix("file:"), s.strip_prefix("env:")) {
+                (Some(path), _) => Self::File(path.trim_start_matches("//").to_string()),
+                (_, Some("")) => bail!("invalid signer env:, expected env:<VAR>"),
+                (_, Some(var)) => Self::Env(var.to_string()),
+                _ => Self::File(s.to_string()),
+            },
+        })
+    }
+}
+
+/// Resolves keypair-like options into signers.
+#[derive(Debug, Default, Clone)]
+pub struct SignerConfig {
+    /// Signatures given with `--signer`.
+    pub presigned: Vec<Presigned>,
+    /// Whether transactions are only signed, leaving the signatures of pubkey signers absent.
+    pub sign_only: bool,
+    /// Option whose keypair was read from standard input, which only holds one.
+    stdin_option: RefCell<Option<String>>,
+}
+
+impl SignerConfig {
+    pub fn new(presigned: Vec<Presigned>, sign_only: bool) -> Self {
+        Self {
+            presigned,
+            sign_only,
+            stdin_option: RefCell::default(),
+        }
+    }
+
+    /// Signer given by the signer URI `uri` of the option `name`. A pubkey is signed with its
+    /// `--signer` signature, or left unsigned under `--sign-only`. Fails with an
+    /// [`Error::Signer`].
+    pub fn signer(&self, uri: &str, name: &str) -> Result<Box<dyn Signer>> {
+        self.read_signer(uri, name)
+            .map_err(|err| Error::Signer(err.context(format!("invalid --{}", name))).into())
+    }
+
+    fn read_signer(&self, uri: &str, name: &str) -> Result<Box<dyn Signer>> {
+        let keypair = match uri.parse()? {
+            SignerSource::Pubkey(pubkey) => {
+                if let Some(presigned) = self.presigned.iter().find(|p| p.pubkey == pubkey) {
+                    return Ok(Box::new(Presigner::new(&pubkey, &presigned.signature)));
+                }
+                if self.sign_only {
+                    return Ok(Box::new(NullSigner::new(&pubkey)));
+                }
+                bail!(
+                    "{} is a pubkey, give its keypair or its signature with --signer",
+                    pubkey
+                );
+            }
+            SignerSource::File(path) => read_keypair_file(shellexpand::tilde(&path).as_ref())
+                .map_err(|err| anyhow!("failed to read keypair {}: {}", path, err))?,
+            SignerSource::Prompt => keypair_from_prompt(name)?,
+            SignerSource::Stdin => {
+                if let Some(option) = self.stdin_option.replace(Some(name.to_string())) {
+                    bail!(
+                        "stdin: already holds the keypair of --{}, only one keypair can be read \
+                         from stdin",
+                        option
+                    );
+                }
+                read_keypair(&mut io::stdin())
+                    .map_err(|err| anyhow!("failed to read keypair from stdin: {}", err))?
+            }
+            SignerSource::Env(var) => keypair_from_env(&var)?,
+        };
+        Ok(Box::new(keypair))
+    }
+}
+
+/// Derives the keypair of a seed phrase and optional passphrase typed at the terminal.
+fn keypair_from_prompt(name: &str) -> Result<Keypair> {
+    let seed_phrase = rpassword::prompt_password(format!("Seed phrase for {}: ", name))
+        .context("failed to read the seed phrase")?;
+    let passphrase =
+        rpassword::prompt_password(format!("BIP39 passphrase for {} (empty for none): ", name))
+            .context("failed to read the passphrase")?;
+
+    keypair_from_seed_phrase(&seed_phrase, &passphrase, name)
+}
+
+/// Derives the keypair of a seed phrase, checked to be an English BIP39 mnemonic with a valid
+/// checksum so a mistyped word is not taken for a different keypair.
+fn keypair_from_seed_phrase(seed_phrase: &str, passphrase: &str, name: &str) -> Result<Keypair> {
+    let seed_phrase = seed_phrase.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mnemonic = Mnemonic::from_phrase(&seed_phrase, Language::English)
+        .map_err(|err| anyhow!("invalid seed phrase for {}: {}", name, err))?;
+    keypair_from_seed_phrase_and_passphrase(mnemonic.phrase(), passphrase)
+        .map_err(|err| anyhow!("invalid seed phrase for {}: {}", name, err))
+}
+
+/// Reads the keypair held by the environment variable `var`, as a JSON byte array or a base58
+/// secret key. Errors never include the value.
+fn keypair_from_env(var: &str) -> Result<Keypair> {
+    let value = env::var(var).with_context(|| format!("failed to read ${}", var))?;
+    let value = value.trim();
+
+    let bytes = if value.starts_with('[') {
+        serde_json::from_str::<Vec<u8>>(value)
+            .map_err(|_| anyhow!("${} is not a JSON keypair", var))?
+    } else {
+        bs58::decode(value)
+            .into_vec()
+            .map_err(|_| anyhow!("${} is neither a JSON keypair nor base58", var))?
+    };
+    Keypair::from_bytes(&bytes).map_err(|_| anyhow!("${} does not hold a valid keypair", var))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_presigned() {
+        let presigned = Presigned {
+            pubkey: Pubkey::new_unique(),
+            signature: Signature::new_unique(),
+        };
+
+        assert_eq!(
+            presigned.to_string().parse::<Presigned>().unwrap(),
+            presigned
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_presigned() {
+        let pubkey = Pubkey::new_unique();
+        let signature = Signature::new_unique();
+        for presigned in [
+            pubkey.to_string(),
+            format!("{}:{}", pubkey, signature),
+            format!("{}={}", pubkey, pubkey),
+            format!("{}={}", signature, signature),
+            format!("={}", signature),
+        ] {
+            assert!(presigned.parse::<Presigned>().is_err(), "{}", presigned);
+        }
+    }
+
+    #[test]
+    fn signs_pubkeys_with_their_presigned_signature() {
+        let keypair = Keypair::new();
+        let presigned = Presigned {
+            pubkey: keypair.pubkey(),
+            signature: keypair.sign_message(b"message"),
+        };
+        let config = SignerConfig::new(vec![presigned], false);
+
+        let signer = config
+            .signer(&presigned.pubkey.to_string(), "keypair")
+            .unwrap();
+        assert_eq!(signer.pubkey(), presigned.pubkey);
+        assert_eq!(signer.sign_message(b"message"), presigned.signature);
+        assert!(signer.try_sign_message(b"other message").is_err());
+    }
+
+    #[test]
+    fn leaves_pubkeys_unsigned_under_sign_only() {
+        let pubkey = Pubkey::new_unique();
+        let config = SignerConfig::new(vec![], true);
+
+        let signer = config.signer(&pubkey.to_string(), "keypair").unwrap();
+        assert_eq!(signer.pubkey(), pubkey);
+        assert_eq!(signer.sign_message(b"message"), Signature::default());
+    }
+
+    #[test]
+    fn rejects_pubkeys_without_a_signature() {
+        let config = SignerConfig::new(vec![], false);
+
+        let err = config
+            .signer(&Pubkey::new_unique().to_string(), "keypair")
+            .unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(Error::Signer(_))));
+    }
+
+    #[test]
+    fn parses_signer_sources() {
+        let pubkey = Pubkey::new_unique();
+        for (uri, source) in [
+            ("id.json", SignerSource::File("id.json".to_string())),
+            ("~/id.json", SignerSource::File("~/id.json".to_string())),
+            ("file:id.json", SignerSource::File("id.json".to_string())),
+            (
+                "file:///id.json",
+                SignerSource::File("/id.json".to_string()),
+            ),
+            ("prompt:", SignerSource::Prompt),
+            ("prompt://", SignerSource::Prompt),
+            ("stdin:", SignerSource::Stdin),
+            ("stdin://", SignerSource::Stdin),
+            ("env:KEYPAIR", SignerSource::Env("KEYPAIR".to_string())),
+            (&pubkey.to_string(), SignerSource::Pubkey(pubkey)),
+        ] {
+            assert_eq!(uri.parse::<SignerSource>().unwrap(), source, "{}", uri);
+        }
+    }
+
+    #[test]
+    fn rejects_unsupported_signer_sources() {
+        assert!("usb://ledger".parse::<SignerSource>().is_err());
+        assert!("env:".parse::<SignerSource>().is_err());
+    }
+
+    #[test]
+    fn derives_keypairs_from_seed_phrases() {
+        let seed_phrase = "abandon abandon abandon abandon abandon abandon abandon abandon \
+                           abandon abandon abandon about";
+
+        let keypair = keypair_from_seed_phrase(seed_phrase, "", "keypair").unwrap();
+        assert_eq!(
+            keypair.pubkey(),
+            keypair_from_seed_phrase_and_passphrase(seed_phrase, "")
+                .unwrap()
+                .pubkey()
+        );
+    }
+
+    #[test]
+    fn rejects_seed_phrases_with_a_bad_checksum() {
+        let seed_phrase = ["abandon"; 12].join(" ");
+
+        let err = keypair_from_seed_phrase(&seed_phrase, "", "keypair").unwrap_err();
+        assert!(err.to_string().contains("invalid seed phrase for keypair"));
+        assert!(keypair_from_seed_phrase("abandon abandon notaword", "", "keypair").is_err());
+    }
+
+    #[test]
+    fn reads_keypairs_from_env() {
+        let keypair = Keypair::new();
+        env::set_var(
+            "SPL_FAUCET_TEST_JSON_KEYPAIR",
+            format!(" {:?}\n", keypair.to_bytes().to_vec()),
+        );
+        env::set_var("SPL_FAUCET_TEST_BASE58_KEYPAIR", keypair.to_base58_string());
+
+        for var in [
+            "SPL_FAUCET_TEST_JSON_KEYPAIR",
+            "SPL_FAUCET_TEST_BASE58_KEYPAIR",
+        ] {
+            assert_eq!(keypair_from_env(var).unwrap(), keypair, "{}", var);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_keypairs_from_env_without_their_value() {
+        for (var, value) in [
+            ("SPL_FAUCET_TEST_TRUNCATED_KEYPAIR", "[1, 2, 3]"),
+            ("SPL_FAUCET_TEST_INVALID_JSON_KEYPAIR", "[secret"),
+            ("SPL_FAUCET_TEST_INVALID_BASE58_KEYPAIR", "secret0OIl"),
+        ] {
+            env::set_var(var, value);
+            let err = format!("{:#}", keypair_from_env(var).unwrap_err());
+            assert!(
+                !err.contains("secret") && !err.contains("1, 2, 3"),
+                "{}",
+                err
+            );
+        }
+        assert!(keypair_from_env("SPL_FAUCET_TEST_UNSET_KEYPAIR").is_err());
+    }
+
+    #[test]
+    fn reads_one_keypair_from_stdin() {
+        let config = SignerConfig::new(vec![], false);
+        config.stdin_option.replace(Some("keypair".to_string()));
+
+        let err = config.signer("stdin:", "fee-payer").unwrap_err();
+        assert!(matches!(err.downcast_ref(), Some(Error::Signer(_))));
+        assert!(format!("{:#}", err).contains("--keypair"));
+    }
+}
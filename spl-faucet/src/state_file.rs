@@ -0,0 +1,48 @@
+//! Reading and writing the files `spl-faucet` keeps its state in, the config, registry,
+//! resolved and batch results files, which read as empty until first written.
+
+use std::{fs, io, path::Path};
+
+use anyhow::{Context, Result};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Contents of the file at `path`, none if it does not exist.
+pub fn read(path: &Path) -> Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err).with_context(|| format!("failed to read {}", path.display())),
+    }
+}
+
+/// Writes `contents` to the file at `path`, creating its directory if needed.
+pub fn write(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    fs::write(path, contents).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Reads the JSON file at `path`, the default value if it does not exist.
+pub fn load_json<T: DeserializeOwned + Default>(path: &Path) -> Result<T> {
+    match read(path)? {
+        Some(contents) => serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse {}", path.display())),
+        None => Ok(T::default()),
+    }
+}
+
+/// Reads the TOML file at `path`, the default value if it does not exist.
+pub fn load_toml<T: DeserializeOwned + Default>(path: &Path) -> Result<T> {
+    match read(path)? {
+        Some(contents) => {
+            toml::from_str(&contents).with_context(|| format!("failed to parse {}", path.display()))
+        }
+        None => Ok(T::default()),
+    }
+}
+
+/// Writes `value` to the file at `path` as pretty-printed JSON.
+pub fn save_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    write(path, serde_json::to_string_pretty(value)? + "\n")
+}
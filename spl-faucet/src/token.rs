@@ -0,0 +1,260 @@
+//! Token program selection and account sizing for spl-token and Token-2022 mints.
+
+use std::{fmt, str::FromStr};
+
+use anyhow::{bail, Result};
+use solana_sdk::{instruction::Instruction, program_pack::Pack, pubkey::Pubkey};
+use spl_token_2022::{
+    extension::{interest_bearing_mint, metadata_pointer, transfer_fee, ExtensionType},
+    state::{Account, Mint},
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TokenProgram {
+    #[default]
+    SplToken,
+    SplToken2022,
+}
+
+impl TokenProgram {
+    pub fn id(&self) -> Pubkey {
+        match self {
+            Self::SplToken => spl_token::ID,
+            Self::SplToken2022 => spl_token_2022::ID,
+        }
+    }
+
+    /// Token program owning an account, e.g. a mint.
+    pub fn from_owner(owner: &Pubkey) -> Result<Self> {
+        if *owner == spl_token::ID {
+            Ok(Self::SplToken)
+        } else if *owner == spl_token_2022::ID {
+            Ok(Self::SplToken2022)
+        } else {
+            bail!("account owner {} is not a token program", owner)
+        }
+    }
+
+    /// Size of a mint with `extensions`.
+    pub fn mint_len(&self, extensions: &[ExtensionType]) -> Result<usize> {
+        match self {
+            Self::SplToken => Ok(Mint::LEN),
+            Self::SplToken2022 => Ok(ExtensionType::try_calculate_account_len::<Mint>(
+                extensions,
+            )?),
+        }
+    }
+
+    /// Size of an associated token account for a mint with `mint_extensions`.
+    pub fn associated_token_account_len(&self, mint_extensions: &[ExtensionType]) -> Result<usize> {
+        match self {
+            Self::SplToken => Ok(Account::LEN),
+            Self::SplToken2022 => {
+                let mut extensions =
+                    ExtensionType::get_required_init_account_extensions(mint_extensions);
+                // Associated token accounts are always initialized with an immutable owner.
+                extensions.push(ExtensionType::ImmutableOwner);
+                Ok(ExtensionType::try_calculate_account_len::<Account>(
+                    &extensions,
+                )?)
+            }
+        }
+    }
+}
+
+impl FromStr for TokenProgram {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "spl-token" => Ok(Self::SplToken),
+            "spl-token-2022" => Ok(Self::SplToken2022),
+            _ => bail!(
+                "invalid token program {}, expected spl-token or spl-token-2022",
+                s
+            ),
+        }
+    }
+}
+
+impl fmt::Display for TokenProgram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SplToken => write!(f, "spl-token"),
+            Self::SplToken2022 => write!(f, "spl-token-2022"),
+        }
+    }
+}
+
+/// Token-2022 extensions initialized on a new faucet mint.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MintExtensions {
+    pub transfer_fee: Option<TransferFee>,
+    /// Interest rate in basis points, see `interest_bearing_mint`.
+    pub interest_rate: Option<i16>,
+    /// Point the mint at itself for its metadata, which is then initialized in the mint, see
+    /// [`crate::metadata`].
+    pub metadata_pointer: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferFee {
+    pub basis_points: u16,
+    /// Maximum fee per transfer in base units.
+    pub maximum_fee: u64,
+}
+
+impl MintExtensions {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Fixed-size extensions the mint account must be allocated for.
+    pub fn extension_types(&self) -> Vec<ExtensionType> {
+        let mut extension_types = vec![];
+        if self.transfer_fee.is_some() {
+            extension_types.push(ExtensionType::TransferFeeConfig);
+        }
+        if self.interest_rate.is_some() {
+            extension_types.push(ExtensionType::InterestBearingConfig);
+        }
+        if self.metadata_pointer {
+            extension_types.push(ExtensionType::MetadataPointer);
+        }
+        extension_types
+    }
+
+    /// Instructions initializing the extensions on `mint`, to run between its account
+    /// creation and `initialize_mint2`.
+    ///
+    /// `authority` can update the fee, rate and metadata pointer and withdraw withheld fees.
+    pub fn initialize_ixs(
+        &self,
+        token_program_id: &Pubkey,
+        mint: &Pubkey,
+        authority: &Pubkey,
+    ) -> Result<Vec<Instruction>> {
+        let mut ixs = vec![];
+        if let Some(transfer_fee) = &self.transfer_fee {
+            ixs.push(transfer_fee::instruction::initialize_transfer_fee_config(
+                token_program_id,
+                mint,
+                Some(authority),
+                Some(authority),
+                transfer_fee.basis_points,
+                transfer_fee.maximum_fee,
+            )?);
+        }
+        if let Some(interest_rate) = self.interest_rate {
+            ixs.push(interest_bearing_mint::instruction::initialize(
+                token_program_id,
+                mint,
+                Some(*authority),
+                interest_rate,
+            )?);
+        }
+        if self.metadata_pointer {
+            ixs.push(metadata_pointer::instruction::initialize(
+                token_program_id,
+                mint,
+                Some(*authority),
+                Some(*mint),
+            )?);
+        }
+        Ok(ixs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_token_programs() {
+        for token_program in [TokenProgram::SplToken, TokenProgram::SplToken2022] {
+            assert_eq!(
+                token_program.to_string().parse::<TokenProgram>().unwrap(),
+                token_program
+            );
+            assert_eq!(
+                TokenProgram::from_owner(&token_program.id()).unwrap(),
+                token_program
+            );
+        }
+        assert!("token".parse::<TokenProgram>().is_err());
+        assert!(TokenProgram::from_owner(&Pubkey::new_unique()).is_err());
+    }
+
+    #[test]
+    fn lists_extension_types_of_set_extensions() {
+        assert!(MintExtensions::default().is_empty());
+        assert!(MintExtensions::default().extension_types().is_empty());
+
+        let extensions = MintExtensions {
+            metadata_pointer: true,
+            ..MintExtensions::default()
+        };
+        assert!(!extensions.is_empty());
+        assert_eq!(
+            extensions.extension_types(),
+            [ExtensionType::MetadataPointer]
+        );
+
+        let extensions = MintExtensions {
+            transfer_fee: Some(TransferFee {
+                basis_points: 100,
+                maximum_fee: 1_000,
+            }),
+            interest_rate: Some(500),
+            metadata_pointer: true,
+        };
+        assert_eq!(
+            extensions.extension_types(),
+            [
+                ExtensionType::TransferFeeConfig,
+                ExtensionType::InterestBearingConfig,
+                ExtensionType::MetadataPointer,
+            ]
+        );
+    }
+
+    #[test]
+    fn sizes_mints_with_their_extensions() {
+        let extensions = [ExtensionType::TransferFeeConfig];
+
+        assert_eq!(TokenProgram::SplToken.mint_len(&[]).unwrap(), Mint::LEN);
+        assert_eq!(
+            TokenProgram::SplToken.mint_len(&extensions).unwrap(),
+            Mint::LEN
+        );
+        assert_eq!(TokenProgram::SplToken2022.mint_len(&[]).unwrap(), Mint::LEN);
+        // Padded to the size of a token account, then the account type and the extension.
+        assert_eq!(
+            TokenProgram::SplToken2022.mint_len(&extensions).unwrap(),
+            Account::LEN + 1 + 4 + 108
+        );
+    }
+
+    #[test]
+    fn sizes_associated_token_accounts_with_an_immutable_owner() {
+        assert_eq!(
+            TokenProgram::SplToken
+                .associated_token_account_len(&[ExtensionType::TransferFeeConfig])
+                .unwrap(),
+            Account::LEN
+        );
+        assert_eq!(
+            TokenProgram::SplToken2022
+                .associated_token_account_len(&[])
+                .unwrap(),
+            Account::LEN + 1 + 4
+        );
+        // Transfer fee mints need a withheld amount on every token account.
+        assert_eq!(
+            TokenProgram::SplToken2022
+                .associated_token_account_len(&[ExtensionType::TransferFeeConfig])
+                .unwrap(),
+            Account::LEN + 1 + 4 + 4 + 8
+        );
+    }
+}